@@ -0,0 +1,64 @@
+//! Compare the full-FFT spectrum with the sliding-DFT spectrum.
+//!
+//! Run with `cargo bench --features sliding-dft --bench spectrum`.
+
+use std::f32::consts::PI;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use chord_detector::Chromagram;
+
+const SAMPLE_RATE: usize = 44_100;
+const SECONDS: usize = 20;
+
+/// Stream 20 s of audio, forcing an emission every `frames_per_emission`
+/// frames with `flush`.
+fn time(incremental: bool, frame: usize, frames_per_emission: usize) -> (Duration, usize) {
+    let (downsample_factor, buffer_size) = (4, 8192);
+    let mut chroma = Chromagram::builder()
+        .frame_size(frame)
+        .downsample_factor(downsample_factor)
+        .buffer_size(buffer_size)
+        .incremental_spectrum(incremental)
+        .build()
+        .unwrap();
+    let samples: Vec<f32> = (0..SAMPLE_RATE * SECONDS)
+        .map(|i| (2.0 * PI * 261.63 * i as f32 / SAMPLE_RATE as f32).sin())
+        .collect();
+
+    let start = Instant::now();
+    let mut emissions = 0;
+    for (i, f) in samples.chunks_exact(frame).enumerate() {
+        if let Some(c) = chroma.next(f).unwrap() {
+            black_box(c);
+            emissions += 1;
+        }
+        if (i + 1) % frames_per_emission == 0 {
+            if let Some(c) = chroma.flush() {
+                black_box(c);
+                emissions += 1;
+            }
+        }
+    }
+    (start.elapsed(), emissions)
+}
+
+fn main() {
+    println!(
+        "{:>6} {:>8} {:>10} {:>12} {:>12}",
+        "hop", "overlap", "emissions", "fft", "sliding"
+    );
+    for (frame, frames_per_emission) in [(1024, 4), (1024, 2), (1024, 1), (512, 1), (256, 1)] {
+        // Hop in analysed (downsampled) samples against an 8192-sample window
+        let hop = frame * frames_per_emission / 4;
+        let overlap = 1.0 - hop as f64 / 8192.0;
+        let (fft, emissions) = time(false, frame, frames_per_emission);
+        let (sliding, _) = time(true, frame, frames_per_emission);
+        println!(
+            "{hop:>6} {:>7.1}% {emissions:>10} {:>10.1}ms {:>10.1}ms",
+            overlap * 100.0,
+            fft.as_secs_f64() * 1e3,
+            sliding.as_secs_f64() * 1e3,
+        );
+    }
+}
@@ -0,0 +1,27 @@
+//! Print the chords heard on the default input device.
+//!
+//! Run with `cargo run --example cpal_chords --features cpal`.
+
+use chord_detector::{ChordDetector, Chromagram, CpalChordSource};
+use cpal::traits::HostTrait;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let device = cpal::default_host()
+        .default_input_device()
+        .ok_or("no input device available")?;
+
+    let source = CpalChordSource::build(
+        &device,
+        Chromagram::builder().frame_size(1024),
+        ChordDetector::builder().bleed(0.15),
+    )?;
+    println!("listening at {} Hz, Ctrl-C to stop", source.sample_rate());
+
+    while let Some(result) = source.recv() {
+        match result {
+            Ok(chord) => println!("{chord} ({:.2})", chord.confidence),
+            Err(e) => eprintln!("stream error: {e}"),
+        }
+    }
+    Ok(())
+}
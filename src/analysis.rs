@@ -0,0 +1,94 @@
+//! Analysis
+//!
+//! High-level helpers that run the chromagram and chord detector over whole
+//! audio clips.
+
+use thiserror::Error;
+
+use crate::chord_detector::{Chord, ChordDetector, ChordError};
+use crate::chromagram::{Chromagram, ChromagramError};
+
+const SEMITONES: usize = 12;
+
+/// Default number of loudest chromagrams averaged by [`detect_robust`].
+pub const DEFAULT_TOP_FRAMES: usize = 8;
+
+/// Errors returned by the clip analysis helpers.
+#[derive(Debug, Error)]
+pub enum AnalysisError {
+    /// The chromagram pipeline failed.
+    #[error(transparent)]
+    Chromagram(#[from] ChromagramError),
+
+    /// Chord detection failed.
+    #[error(transparent)]
+    Chord(#[from] ChordError),
+
+    /// The clip was too short to produce a single chromagram.
+    #[error("clip too short: no chromagram was produced")]
+    NoChromagram,
+}
+
+/// Detect the sustained chord of a mono clip, averaging the
+/// [`DEFAULT_TOP_FRAMES`] loudest chromagrams.
+///
+/// See [`detect_robust_top_n`].
+pub fn detect_robust(samples: &[f32], sample_rate: usize) -> Result<Chord, AnalysisError> {
+    detect_robust_top_n(samples, sample_rate, DEFAULT_TOP_FRAMES)
+}
+
+/// Detect the sustained chord of a mono clip from the average of its `n`
+/// loudest chromagrams.
+///
+/// The clip is streamed through a default [`Chromagram`] at `sample_rate` and
+/// every emitted chromagram is ranked by energy (sum of squared bins). Only
+/// the `n` strongest are averaged before detection, which rejects silence and
+/// quiet tails without any gating threshold. Samples past the last whole frame
+/// are ignored.
+///
+/// Returns:
+/// - `Err(NoChromagram)` if the clip is too short to emit a chromagram.
+/// - `Err(Chord(InvalidArgument))` if `n == 0`.
+pub fn detect_robust_top_n(
+    samples: &[f32],
+    sample_rate: usize,
+    n: usize,
+) -> Result<Chord, AnalysisError> {
+    if n == 0 {
+        return Err(ChordError::InvalidArgument {
+            arg: "n",
+            msg: "must be >= 1".to_string(),
+        }
+        .into());
+    }
+
+    let mut chromagram = Chromagram::builder().sampling_rate(sample_rate).build()?;
+    let frame_size = chromagram.frame_size();
+
+    let mut frames = Vec::new();
+    for frame in samples.chunks_exact(frame_size) {
+        if let Some(chroma) = chromagram.next(frame)? {
+            frames.push(chroma);
+        }
+    }
+    if frames.is_empty() {
+        return Err(AnalysisError::NoChromagram);
+    }
+
+    frames.sort_by(|a, b| energy(b).total_cmp(&energy(a)));
+    let top = &frames[..n.min(frames.len())];
+
+    let mut average = [0.0; SEMITONES];
+    for chroma in top {
+        for (avg, &c) in average.iter_mut().zip(chroma) {
+            *avg += c / top.len() as f32;
+        }
+    }
+
+    Ok(ChordDetector::new().detect_chord(&average)?)
+}
+
+#[inline]
+fn energy(chroma: &[f32; SEMITONES]) -> f32 {
+    chroma.iter().map(|c| c * c).sum()
+}
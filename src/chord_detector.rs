@@ -5,13 +5,26 @@
 //! Ported and modified from C++ version by Adam Stark, Queen Mary University of London.
 //! https://github.com/adamstark/Chord-Detector-and-Chromagram
 
-use std::fmt::Display;
+use alloc::collections::VecDeque;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use alloc::{format, vec};
+use core::cmp::Ordering;
+use core::fmt::Display;
+use core::str::FromStr;
 use thiserror::Error;
 
+use crate::math::sqrtf;
+
 const SEMITONES: usize = 12;
 
 /// Number of chord qualities
-const NUM_CHORD_KINDS: usize = 10;
+#[cfg(not(feature = "extended_chords"))]
+const NUM_CHORD_KINDS: usize = 12;
+
+/// Number of chord qualities, including the sixth, add9 and ninth chords
+#[cfg(feature = "extended_chords")]
+const NUM_CHORD_KINDS: usize = 16;
 
 /// Total number of chords (root × quality)
 const NUM_CHORDS: usize = SEMITONES * NUM_CHORD_KINDS;
@@ -21,6 +34,10 @@ const CHORD_KINDS: [ChordKind; NUM_CHORD_KINDS] = [
     ChordKind::Major,
     ChordKind::Minor,
     ChordKind::PowerFifth,
+    #[cfg(feature = "extended_chords")]
+    ChordKind::Major6,
+    #[cfg(feature = "extended_chords")]
+    ChordKind::Minor6,
     ChordKind::DominantSeventh,
     ChordKind::MajorSeventh,
     ChordKind::MinorSeventh,
@@ -28,20 +45,41 @@ const CHORD_KINDS: [ChordKind; NUM_CHORD_KINDS] = [
     ChordKind::Augmented,
     ChordKind::SuspendedSecond,
     ChordKind::SuspendedFourth,
+    ChordKind::HalfDiminished,
+    ChordKind::DiminishedSeventh,
+    #[cfg(feature = "extended_chords")]
+    ChordKind::Add9,
+    #[cfg(feature = "extended_chords")]
+    ChordKind::DominantNinth,
 ];
 
 /// (quality, bias, number_of_intervals)
+///
+/// Sixth chords share their pitch classes with a seventh chord on another
+/// root and score exactly the same, so they are listed before the sevenths:
+/// exact ties go to the earlier entry, and no bias can separate two zero
+/// residuals.
 const CHORD_SPECS: &[(ChordKind, f32, f32)] = &[
-    (ChordKind::Major,           1.06, 3.0),
-    (ChordKind::Minor,           1.06, 3.0),
-    (ChordKind::PowerFifth,      1.005, 2.0),
-    (ChordKind::DominantSeventh, 1.06, 4.0),
-    (ChordKind::MajorSeventh,    1.00, 4.0),
-    (ChordKind::MinorSeventh,    1.06, 4.0),
-    (ChordKind::Diminished,      1.05, 3.0),
-    (ChordKind::Augmented,       1.055, 3.0),
-    (ChordKind::SuspendedSecond, 1.0, 3.0),
-    (ChordKind::SuspendedFourth, 1.0, 3.0),
+    (ChordKind::Major,             1.06, 3.0),
+    (ChordKind::Minor,             1.06, 3.0),
+    (ChordKind::PowerFifth,        1.005, 2.0),
+    #[cfg(feature = "extended_chords")]
+    (ChordKind::Major6,            1.06, 4.0),
+    #[cfg(feature = "extended_chords")]
+    (ChordKind::Minor6,            1.06, 4.0),
+    (ChordKind::DominantSeventh,   1.06, 4.0),
+    (ChordKind::MajorSeventh,      1.00, 4.0),
+    (ChordKind::MinorSeventh,      1.06, 4.0),
+    (ChordKind::Diminished,        1.05, 3.0),
+    (ChordKind::Augmented,         1.055, 3.0),
+    (ChordKind::SuspendedSecond,   1.0, 3.0),
+    (ChordKind::SuspendedFourth,   1.0, 3.0),
+    (ChordKind::HalfDiminished,    1.06, 4.0),
+    (ChordKind::DiminishedSeventh, 1.05, 4.0),
+    #[cfg(feature = "extended_chords")]
+    (ChordKind::Add9,              1.06, 4.0),
+    #[cfg(feature = "extended_chords")]
+    (ChordKind::DominantNinth,     1.06, 5.0),
 ];
 
 /// Intervals (in semitones) matching `CHORD_SPECS` order
@@ -49,6 +87,10 @@ const CHORD_INTERVALS: [&[usize]; NUM_CHORD_KINDS] = [
     &[0, 4, 7],
     &[0, 3, 7],
     &[0, 7],
+    #[cfg(feature = "extended_chords")]
+    &[0, 4, 7, 9],
+    #[cfg(feature = "extended_chords")]
+    &[0, 3, 7, 9],
     &[0, 4, 7, 10],
     &[0, 4, 7, 11],
     &[0, 3, 7, 10],
@@ -56,31 +98,198 @@ const CHORD_INTERVALS: [&[usize]; NUM_CHORD_KINDS] = [
     &[0, 4, 8],
     &[0, 2, 7],
     &[0, 5, 7],
+    &[0, 3, 6, 10],
+    &[0, 3, 6, 9],
+    #[cfg(feature = "extended_chords")]
+    &[0, 4, 7, 14],
+    #[cfg(feature = "extended_chords")]
+    &[0, 4, 7, 10, 14],
 ];
 
+/// Default bleed suppression factor
+const DEFAULT_BLEED: f32 = 0.157;
+
+/// Default chroma energy below which a frame is considered silent
+const DEFAULT_SILENCE_THRESHOLD: f32 = 1e-4;
+
+/// Share of the chroma sum the loudest bin needs for `detect_pitch` to name
+/// it; above the 1/3 of an evenly voiced triad
+const PITCH_DOMINANCE: f32 = 0.4;
+
+/// Default offset in semitones from each bin to the bin its bleed is
+/// subtracted from: a perfect fourth down
+const DEFAULT_BLEED_INTERVAL: i8 = -5;
+
+/// Fraction of the loudest bin below which a chord tone counts as missing
+const MISSING_TONE_RATIO: f32 = 0.1;
+
+/// Pitch-class offset of partials 1..=6 above their fundamental, to the
+/// nearest semitone; the partials credited by `TemplateModel::Harmonic`
+const PARTIAL_PITCH_CLASSES: [usize; 6] = [0, 0, 7, 0, 4, 7];
+
+/// Weight of partial `h` relative to partial `h - 1` under `TemplateModel::Harmonic`
+const TEMPLATE_HARMONIC_DECAY: f32 = 0.7;
+
 /// A single chromagram: energy for each of the 12 semitones
 type Chromagram = [f32; SEMITONES];
 
-/// Precomputed chord profile + inverse normalizer
+/// Precomputed chord profile + squared inverse normalizer
 #[derive(Copy, Clone)]
 struct PrecalcProfile {
     weights: Chromagram,
-    inv_norm: f32,
+    inv_norm_sq: f32,
 }
 
 /// Represents a musical chord detected from an audio signal.
 #[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Chord {
     /// The root note of the detected chord.
     pub root: NoteName,
     /// The quality (e.g., Major, Minor) of the detected chord.
     pub quality: ChordKind,
     /// A confidence score for the detection, where lower values indicate a better match.
+    ///
+    /// With `ChordDetectorBuilder::normalized_confidence` this is instead a
+    /// similarity in 0.0..=1.0 where higher is better.
     pub confidence: f32,
+    /// The pitch class in the bass, when known. `None` when no bass
+    /// information is available (see `ChordDetectorBuilder::detect_bass`).
+    pub bass: Option<NoteName>,
+}
+
+impl Chord {
+    /// Canonical chord symbol: the root's sharp spelling followed by the
+    /// quality suffix, e.g. `C`, `C#m7`, `Gsus4` or `A5`.
+    ///
+    /// Returns `N.C.` (no chord) for an unknown root. Custom chords read as
+    /// `D(custom 1)`; use [`ChordDetector::chord_name`] for their template
+    /// name. The bass is not included, see the `Display` impl for that.
+    pub fn name(&self) -> String {
+        match (self.root, self.quality) {
+            (NoteName::Unknown, _) => "N.C.".to_string(),
+            (root, ChordKind::Custom(idx)) => format!("{}(custom {idx})", root.symbol()),
+            (root, quality) => format!("{}{}", root.symbol(), quality.suffix()),
+        }
+    }
+
+    /// The inversion implied by the bass note.
+    ///
+    /// Returns `Some(0)` for root position, `Some(1)` for first inversion
+    /// (third in the bass), `Some(2)` for second and `Some(3)` for third
+    /// inversion; the ninth of a ninth chord gives `Some(4)`. Returns `None`
+    /// when the bass is unknown or is not a tone of the chord, and always for
+    /// custom chords.
+    pub fn inversion(&self) -> Option<u8> {
+        let root = self.root.to_semitone()?;
+        let bass = self.bass?.to_semitone()?;
+        let offset = (bass + SEMITONES - root) % SEMITONES;
+        self.quality
+            .intervals()
+            .iter()
+            .position(|&i| i % SEMITONES == offset)
+            .map(|pos| pos as u8)
+    }
+
+    /// MIDI note numbers of the chord tones, root first, with the root in
+    /// `octave`.
+    ///
+    /// Uses scientific pitch notation, so C4 is middle C (60) and C major in
+    /// octave 4 is `[60, 64, 67]`. Upper tones stay above the root, reaching
+    /// into the next octave if needed. Tones that would exceed MIDI note 127
+    /// are left out, so octave 9 and above return fewer notes or none. Empty
+    /// for an unknown root and for custom chords.
+    pub fn midi_notes(&self, octave: u8) -> Vec<u8> {
+        let Some(root) = self.root.to_semitone() else {
+            return Vec::new();
+        };
+        let base = (usize::from(octave) + 1) * SEMITONES + root;
+        self.quality
+            .intervals()
+            .iter()
+            .map(|&off| base + off)
+            .take_while(|&note| note <= 127)
+            .map(|note| note as u8)
+            .collect()
+    }
+
+    /// Pitch classes of the chord tones, in the order of
+    /// [`ChordKind::intervals`], so the root comes first.
+    ///
+    /// C major gives `[C, E, G]`. Empty for an unknown root and for custom
+    /// chords.
+    pub fn pitch_classes(&self) -> Vec<NoteName> {
+        let Some(root) = self.root.to_semitone() else {
+            return Vec::new();
+        };
+        self.quality
+            .intervals()
+            .iter()
+            .map(|&off| NoteName::from_idx((root + off) % SEMITONES))
+            .collect()
+    }
+
+    /// Every labeling of this chord's pitch-class set, starting with `self`.
+    ///
+    /// Symmetric shapes have several valid names: C augmented is also E and
+    /// G# augmented, and Csus2 is Gsus4. Other entries share this chord's
+    /// confidence and bass. `NoteName` already stands for both enharmonic
+    /// spellings (`Ds` is D#/Eb), so ordinary chords return only `self`, and
+    /// so do custom chords, whose tones are only known to their detector.
+    pub fn equivalent_spellings(&self) -> Vec<Chord> {
+        let mut spellings = vec![*self];
+        let Some(root) = self.root.to_semitone() else {
+            return spellings;
+        };
+
+        let mask = tone_mask(root, self.quality);
+        for &quality in &CHORD_KINDS {
+            for other in 0..SEMITONES {
+                if (other, quality) != (root, self.quality) && tone_mask(other, quality) == mask {
+                    spellings.push(Chord {
+                        root: NoteName::from_idx(other),
+                        quality,
+                        ..*self
+                    });
+                }
+            }
+        }
+        spellings
+    }
+}
+
+/// Bitmask of the pitch classes in a chord
+fn tone_mask(root: usize, quality: ChordKind) -> u16 {
+    quality
+        .intervals()
+        .iter()
+        .fold(0, |mask, &off| mask | 1 << ((root + off) % SEMITONES))
+}
+
+impl Display for Chord {
+    /// [`Chord::name`] followed by a slash bass when it differs from the
+    /// root, e.g. `C/E`.
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.name())?;
+        if self.root == NoteName::Unknown {
+            return Ok(());
+        }
+        match self.bass {
+            Some(bass) if bass != self.root && bass != NoteName::Unknown => {
+                write!(f, "/{}", bass.symbol())
+            }
+            _ => Ok(()),
+        }
+    }
 }
 
 /// Supported chord qualities
+///
+/// With the `serde` feature, qualities serialize as snake_case names
+/// (`"major"`, `"dominant_seventh"`, `{"custom": 0}`).
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum ChordKind {
     /// Major chord (e.g., C-E-G)
     Major,
@@ -88,6 +297,16 @@ pub enum ChordKind {
     Minor,
     /// Power chord (e.g., C-G)
     PowerFifth,
+    /// Major sixth chord (e.g., C-E-G-A); same pitch classes as the
+    /// minor seventh on its sixth (Am7). Exact ties go to the sixth, unless
+    /// [`ChordDetectorBuilder::weight_bass`] favours a louder A
+    #[cfg(feature = "extended_chords")]
+    Major6,
+    /// Minor sixth chord (e.g., C-Eb-G-A); same pitch classes as the
+    /// half-diminished seventh on its sixth (Am7b5), with ties resolved
+    /// the same way as for `Major6`
+    #[cfg(feature = "extended_chords")]
+    Minor6,
     /// Dominant seventh chord (e.g., C-E-G-Bb)
     DominantSeventh,
     /// Major seventh chord (e.g., C-E-G-B)
@@ -102,38 +321,265 @@ pub enum ChordKind {
     SuspendedSecond,
     /// Suspended fourth chord (e.g., C-F-G)
     SuspendedFourth,
+    /// Half-diminished seventh chord, m7b5 (e.g., C-Eb-Gb-Bb)
+    HalfDiminished,
+    /// Diminished seventh chord (e.g., C-Eb-Gb-Bbb)
+    DiminishedSeventh,
+    /// Major triad with an added ninth (e.g., C-E-G-D)
+    #[cfg(feature = "extended_chords")]
+    Add9,
+    /// Dominant ninth chord (e.g., C-E-G-Bb-D)
+    #[cfg(feature = "extended_chords")]
+    DominantNinth,
+    /// No chord template matched well enough
+    Unknown,
+    /// User-defined template, by position in the list given to
+    /// [`ChordDetectorBuilder::templates`]
+    Custom(u16),
+}
+
+impl ChordKind {
+    /// Every built-in quality, in the order detectors score them. `Unknown`
+    /// and `Custom` are not included.
+    pub const ALL: [ChordKind; NUM_CHORD_KINDS] = CHORD_KINDS;
+
+    /// Semitone offsets of the chord tones from the root, in ascending
+    /// order starting with `0` for the root, e.g. `[0, 4, 7]` for `Major`.
+    /// A ninth is given as `14`, an octave above the second, so reduce
+    /// offsets modulo 12 for pitch classes.
+    ///
+    /// Empty for `Unknown` and `Custom` qualities, whose tones are only
+    /// known to the detector that holds the template.
+    pub fn intervals(&self) -> &'static [usize] {
+        match CHORD_KINDS.iter().position(|k| k == self) {
+            Some(kind_idx) => CHORD_INTERVALS[kind_idx],
+            None => &[],
+        }
+    }
+
+    /// Conventional chord-symbol suffix
+    const fn suffix(self) -> &'static str {
+        match self {
+            ChordKind::Major => "",
+            ChordKind::Minor => "m",
+            ChordKind::PowerFifth => "5",
+            ChordKind::DominantSeventh => "7",
+            ChordKind::MajorSeventh => "maj7",
+            ChordKind::MinorSeventh => "m7",
+            ChordKind::Diminished => "dim",
+            ChordKind::Augmented => "aug",
+            ChordKind::SuspendedSecond => "sus2",
+            ChordKind::SuspendedFourth => "sus4",
+            ChordKind::HalfDiminished => "m7b5",
+            ChordKind::DiminishedSeventh => "dim7",
+            #[cfg(feature = "extended_chords")]
+            ChordKind::Major6 => "6",
+            #[cfg(feature = "extended_chords")]
+            ChordKind::Minor6 => "m6",
+            #[cfg(feature = "extended_chords")]
+            ChordKind::Add9 => "add9",
+            #[cfg(feature = "extended_chords")]
+            ChordKind::DominantNinth => "9",
+            ChordKind::Unknown | ChordKind::Custom(_) => "?",
+        }
+    }
+
+    /// Canonical shorthand name, the inverse of `ChordKind::try_from(&str)`:
+    /// `maj`, `min`, `power`, `7`, `maj7`, `m7`, `dim`, `aug`, `sus2`,
+    /// `sus4`, `m7b5` and `dim7`, plus `6`, `m6`, `add9` and `9` with the
+    /// `extended_chords` feature.
+    ///
+    /// These are the mnemonics used in sample file names, unlike the chord
+    /// symbol suffix of [`Chord::name`]. `Unknown` and custom qualities
+    /// both read as `?`, which parses back as `Unknown`.
+    pub const fn shorthand(&self) -> &'static str {
+        match self {
+            ChordKind::Major => "maj",
+            ChordKind::Minor => "min",
+            ChordKind::PowerFifth => "power",
+            ChordKind::DominantSeventh => "7",
+            ChordKind::MajorSeventh => "maj7",
+            ChordKind::MinorSeventh => "m7",
+            ChordKind::Diminished => "dim",
+            ChordKind::Augmented => "aug",
+            ChordKind::SuspendedSecond => "sus2",
+            ChordKind::SuspendedFourth => "sus4",
+            ChordKind::HalfDiminished => "m7b5",
+            ChordKind::DiminishedSeventh => "dim7",
+            #[cfg(feature = "extended_chords")]
+            ChordKind::Major6 => "6",
+            #[cfg(feature = "extended_chords")]
+            ChordKind::Minor6 => "m6",
+            #[cfg(feature = "extended_chords")]
+            ChordKind::Add9 => "add9",
+            #[cfg(feature = "extended_chords")]
+            ChordKind::DominantNinth => "9",
+            ChordKind::Unknown | ChordKind::Custom(_) => "?",
+        }
+    }
+
+    /// Number of distinct pitch classes in the chord, the length of
+    /// [`ChordKind::intervals`]: 2 for `PowerFifth`, 3 for triads and 4 for
+    /// sevenths (and for the sixth and add9 chords of `extended_chords`,
+    /// with 5 for the dominant ninth). 0 for `Unknown` and `Custom`.
+    pub fn num_tones(&self) -> usize {
+        self.intervals().len()
+    }
+
+    /// Whether this is a two-note dyad, i.e. `PowerFifth`
+    pub fn is_dyad(&self) -> bool {
+        self.num_tones() == 2
+    }
+
+    /// Whether this is a three-note triad, including the suspended chords
+    pub fn is_triad(&self) -> bool {
+        self.num_tones() == 3
+    }
+
+    /// Whether this is a four-note seventh chord
+    pub const fn is_seventh(&self) -> bool {
+        matches!(
+            self,
+            ChordKind::DominantSeventh
+                | ChordKind::MajorSeventh
+                | ChordKind::MinorSeventh
+                | ChordKind::HalfDiminished
+                | ChordKind::DiminishedSeventh
+        )
+    }
 }
 
 impl Display for ChordKind {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "{self:?}")
     }
 }
 
+impl TryFrom<&str> for ChordKind {
+    type Error = ChordError;
+
+    /// Parse a quality shorthand, case-insensitively and ignoring
+    /// surrounding whitespace.
+    ///
+    /// Accepts every [`ChordKind::shorthand`] plus the aliases `m` (minor),
+    /// `5` (power), `dom7` (dominant seventh) and `min7` (minor seventh),
+    /// and with `extended_chords` also `maj6`, `min6` and `dom9`.
+    /// Since case is ignored, `M7` reads as `m7`, a minor seventh.
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        let kind = match s.trim().to_ascii_lowercase().as_str() {
+            "maj" => ChordKind::Major,
+            "min" | "m" => ChordKind::Minor,
+            "power" | "5" => ChordKind::PowerFifth,
+            "7" | "dom7" => ChordKind::DominantSeventh,
+            "maj7" => ChordKind::MajorSeventh,
+            "m7" | "min7" => ChordKind::MinorSeventh,
+            "dim" => ChordKind::Diminished,
+            "aug" => ChordKind::Augmented,
+            "sus2" => ChordKind::SuspendedSecond,
+            "sus4" => ChordKind::SuspendedFourth,
+            "m7b5" => ChordKind::HalfDiminished,
+            "dim7" => ChordKind::DiminishedSeventh,
+            #[cfg(feature = "extended_chords")]
+            "6" | "maj6" => ChordKind::Major6,
+            #[cfg(feature = "extended_chords")]
+            "m6" | "min6" => ChordKind::Minor6,
+            #[cfg(feature = "extended_chords")]
+            "add9" => ChordKind::Add9,
+            #[cfg(feature = "extended_chords")]
+            "9" | "dom9" => ChordKind::DominantNinth,
+            "?" => ChordKind::Unknown,
+            _ => {
+                return Err(ChordError::InvalidArgument {
+                    arg: "quality",
+                    msg: format!("unrecognized chord quality {s:?}"),
+                })
+            }
+        };
+        Ok(kind)
+    }
+}
+
+impl Display for NoteName {
+    /// Sharp spelling (`C`, `C#`, ... `B`); `?` for `Unknown`.
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.symbol())
+    }
+}
+
+impl FromStr for NoteName {
+    type Err = ChordError;
+
+    /// Parse a note letter with an optional sharp (`#`, `♯`) or flat (`b`,
+    /// `♭`); the letter is case-insensitive, so `"C#"`, `"db"` and `"Db"` all
+    /// work, and so do `"E#"` or `"Cb"`. `"?"` parses as `Unknown`, so every
+    /// `NoteName` round-trips through `Display`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ChordError::InvalidArgument {
+            arg: "note",
+            msg: format!("unrecognized note name {s:?}"),
+        };
+        let text = s.trim();
+        if text == "?" {
+            return Ok(NoteName::Unknown);
+        }
+
+        let mut chars = text.chars();
+        let natural: i32 = match chars.next().map(|c| c.to_ascii_uppercase()) {
+            Some('C') => 0,
+            Some('D') => 2,
+            Some('E') => 4,
+            Some('F') => 5,
+            Some('G') => 7,
+            Some('A') => 9,
+            Some('B') => 11,
+            _ => return Err(invalid()),
+        };
+        let accidental = match chars.as_str() {
+            "" => 0,
+            "#" | "♯" => 1,
+            "b" | "♭" => -1,
+            _ => return Err(invalid()),
+        };
+        Ok(NoteName::from_idx((natural + accidental).rem_euclid(SEMITONES as i32) as usize))
+    }
+}
+
 /// Twelve chromatic pitch classes
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+///
+/// With the `serde` feature, notes serialize as lowercase names with sharps
+/// spelled out (`"c"`, `"c_sharp"`, `"unknown"`).
+///
+/// Notes order chromatically from C to B, with `Unknown` last.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum NoteName {
     /// C
     C,
     /// C sharp / D flat
+    #[cfg_attr(feature = "serde", serde(rename = "c_sharp"))]
     Cs,
     /// D
     D,
     /// D sharp / E flat
+    #[cfg_attr(feature = "serde", serde(rename = "d_sharp"))]
     Ds,
     /// E
     E,
     /// F
     F,
     /// F sharp / G flat
+    #[cfg_attr(feature = "serde", serde(rename = "f_sharp"))]
     Fs,
     /// G
     G,
     /// G sharp / A flat
+    #[cfg_attr(feature = "serde", serde(rename = "g_sharp"))]
     Gs,
     /// A
     A,
     /// A sharp / B flat
+    #[cfg_attr(feature = "serde", serde(rename = "a_sharp"))]
     As,
     /// B
     B,
@@ -142,7 +588,23 @@ pub enum NoteName {
 }
 
 impl NoteName {
-    const fn from_idx(idx: usize) -> NoteName {
+    /// The twelve pitch classes in chromatic order from C, without `Unknown`.
+    pub const ALL: [NoteName; SEMITONES] = [
+        NoteName::C,
+        NoteName::Cs,
+        NoteName::D,
+        NoteName::Ds,
+        NoteName::E,
+        NoteName::F,
+        NoteName::Fs,
+        NoteName::G,
+        NoteName::Gs,
+        NoteName::A,
+        NoteName::As,
+        NoteName::B,
+    ];
+
+    pub(crate) const fn from_idx(idx: usize) -> NoteName {
         match idx {
             0 => NoteName::C,
             1 => NoteName::Cs,
@@ -159,6 +621,53 @@ impl NoteName {
             _ => NoteName::Unknown,
         }
     }
+
+    /// Semitone index from C (0..12), or `None` for `Unknown`.
+    pub const fn to_semitone(&self) -> Option<usize> {
+        match *self {
+            NoteName::Unknown => None,
+            note => Some(note as usize),
+        }
+    }
+
+    /// The note a semitone up, wrapping from B to C. `Unknown` stays
+    /// `Unknown`.
+    pub const fn next(&self) -> NoteName {
+        match self.to_semitone() {
+            Some(idx) => NoteName::from_idx((idx + 1) % SEMITONES),
+            None => NoteName::Unknown,
+        }
+    }
+
+    /// The note `semitones` away, up for positive values and down for
+    /// negative ones, wrapping around the octave. `Unknown` stays `Unknown`.
+    pub const fn transpose(&self, semitones: i32) -> NoteName {
+        match self.to_semitone() {
+            Some(idx) => {
+                NoteName::from_idx((idx as i32 + semitones).rem_euclid(SEMITONES as i32) as usize)
+            }
+            None => NoteName::Unknown,
+        }
+    }
+
+    /// Sharp spelling of the note
+    pub(crate) const fn symbol(self) -> &'static str {
+        match self {
+            NoteName::C => "C",
+            NoteName::Cs => "C#",
+            NoteName::D => "D",
+            NoteName::Ds => "D#",
+            NoteName::E => "E",
+            NoteName::F => "F",
+            NoteName::Fs => "F#",
+            NoteName::G => "G",
+            NoteName::Gs => "G#",
+            NoteName::A => "A",
+            NoteName::As => "A#",
+            NoteName::B => "B",
+            NoteName::Unknown => "?",
+        }
+    }
 }
 
 /// Errors when detecting chords
@@ -181,28 +690,557 @@ pub enum ChordError {
         /// A description of the invalid argument.
         msg: String,
     },
+
+    /// The chromagram contained a NaN or infinite bin.
+    #[error("chromagram bin {index} is not finite")]
+    NonFinite {
+        /// Index of the first offending bin.
+        index: usize,
+    },
+}
+
+/// A user-defined chord shape for [`ChordDetectorBuilder::templates`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChordTemplate {
+    /// Display name of the shape, e.g. `"add9"`.
+    pub name: String,
+    /// Semitone offsets of the chord tones from the root, e.g. `[0, 4, 7, 2]`.
+    /// Offsets are taken modulo 12 and duplicates are ignored. Must name at
+    /// least one and at most eleven pitch classes.
+    pub intervals: Vec<usize>,
+    /// Normalization bias; the built-in shapes use 1.0 to 1.06. Larger values
+    /// make the template match more readily. Must be positive and finite.
+    pub bias: f32,
+}
+
+impl ChordTemplate {
+    /// Create a template from a name, chord-tone offsets and bias.
+    pub fn new(name: impl Into<String>, intervals: &[usize], bias: f32) -> Self {
+        ChordTemplate {
+            name: name.into(),
+            intervals: intervals.to_vec(),
+            bias,
+        }
+    }
+}
+
+/// How chord profiles weight the 12 pitch classes.
+///
+/// With the `serde` feature, models serialize as snake_case names.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum TemplateModel {
+    /// Chord tones weigh 1.0, every other pitch class 0.0 (default).
+    #[default]
+    Binary,
+    /// Chord tones weigh 1.0, and the pitch classes of their upper partials
+    /// get decaying partial credit, so overtone energy a real instrument
+    /// adds (the fifth above each tone from the third partial, the major
+    /// third from the fifth) is penalised less.
+    Harmonic,
+}
+
+/// Per-tone fit of a detected chord, from [`ChordDetector::tone_fit`].
+///
+/// Energies are read from the bleed-suppressed working chroma the chord was
+/// scored on, so they are on the scale the templates see rather than that
+/// of the input chromagram.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ToneFit {
+    /// The detected chord
+    pub chord: Chord,
+    /// Every tone of the matched template, root first, with the energy at
+    /// that pitch class; a weak or missing tone is near zero
+    pub tones: Vec<(NoteName, f32)>,
+    /// The loudest pitch class outside the chord and its energy, or `None`
+    /// if no other bin holds any
+    pub extra: Option<(NoteName, f32)>,
+}
+
+/// Complete configuration of a [`ChordDetector`], as set through its
+/// builder.
+///
+/// Take one from [`ChordDetector::config`] and rebuild an identically
+/// configured detector with [`ChordDetector::from_config`]. Each field has
+/// the meaning and default of the builder method of the same name. Only
+/// configuration is captured: streaming history is not.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DetectorConfig {
+    /// See [`ChordDetectorBuilder::bleed`].
+    pub bleed: f32,
+    /// See [`ChordDetectorBuilder::bleed_interval`].
+    pub bleed_interval: i8,
+    /// See [`ChordDetectorBuilder::seventh_bleed`].
+    pub seventh_bleed: Option<f32>,
+    /// See [`ChordDetectorBuilder::exact_confidence`].
+    pub exact_confidence: bool,
+    /// See [`ChordDetectorBuilder::silence_threshold`].
+    pub silence_threshold: f32,
+    /// See [`ChordDetectorBuilder::silence_reset_frames`].
+    pub silence_reset_frames: usize,
+    /// See [`ChordDetectorBuilder::missing_note_tolerance`].
+    pub missing_note_tolerance: Option<usize>,
+    /// See [`ChordDetectorBuilder::allow_rootless`].
+    pub allow_rootless: bool,
+    /// See [`ChordDetectorBuilder::weight_bass`].
+    pub weight_bass: f32,
+    /// See [`ChordDetectorBuilder::min_match_quality`].
+    pub min_match_quality: Option<f32>,
+    /// See [`ChordDetectorBuilder::min_confidence`].
+    pub min_confidence: Option<f32>,
+    /// See [`ChordDetectorBuilder::normalized_confidence`].
+    pub normalized_confidence: bool,
+    /// See [`ChordDetectorBuilder::templates`].
+    pub templates: Vec<ChordTemplate>,
+    /// See [`ChordDetectorBuilder::qualities`]. A detector reports its
+    /// qualities in their usual order, and `None` when it scores all of the
+    /// built-in ones.
+    pub qualities: Option<Vec<ChordKind>>,
+    /// See [`ChordDetectorBuilder::template_model`].
+    pub template_model: TemplateModel,
+    /// See [`ChordDetectorBuilder::detect_bass`].
+    pub detect_bass: bool,
+    /// See [`ChordDetectorBuilder::smoothing_window`].
+    pub smoothing_window: usize,
+    /// See [`ChordDetectorBuilder::smoothing_alpha`].
+    pub smoothing_alpha: f32,
+}
+
+impl Default for DetectorConfig {
+    /// The configuration of `ChordDetector::new()`.
+    fn default() -> Self {
+        ChordDetector::new().config()
+    }
 }
 
 /// Builder for `ChordDetector` to customize bleed factor
 pub struct ChordDetectorBuilder {
     bleed: f32,
+    bleed_interval: i8,
+    seventh_bleed: Option<f32>,
+    exact_confidence: bool,
+    silence_threshold: f32,
+    silence_reset_frames: usize,
+    missing_note_tolerance: Option<usize>,
+    allow_rootless: bool,
+    weight_bass: f32,
+    min_match_quality: Option<f32>,
+    min_confidence: Option<f32>,
+    normalized_confidence: bool,
+    templates: Vec<ChordTemplate>,
+    qualities: Option<Vec<ChordKind>>,
+    template_model: TemplateModel,
+    detect_bass: bool,
+    smoothing_window: usize,
+    smoothing_alpha: f32,
 }
 
 impl ChordDetectorBuilder {
     /// Create a new builder with default bleed = 0.157
     pub fn new() -> Self {
-        ChordDetectorBuilder { bleed: 0.157 }
+        ChordDetectorBuilder {
+            bleed: DEFAULT_BLEED,
+            bleed_interval: DEFAULT_BLEED_INTERVAL,
+            seventh_bleed: None,
+            exact_confidence: false,
+            silence_threshold: DEFAULT_SILENCE_THRESHOLD,
+            silence_reset_frames: 0,
+            missing_note_tolerance: None,
+            allow_rootless: false,
+            weight_bass: 0.0,
+            min_match_quality: None,
+            min_confidence: None,
+            normalized_confidence: false,
+            templates: Vec::new(),
+            qualities: None,
+            template_model: TemplateModel::Binary,
+            detect_bass: false,
+            smoothing_window: 1,
+            smoothing_alpha: 1.0,
+        }
     }
 
-    /// Set the bleed suppression factor (0.0..1.0)
+    /// Set the bleed suppression factor (0.0..=1.0).
+    ///
+    /// Values outside that range, or non-finite ones, are rejected by
+    /// [`ChordDetectorBuilder::try_build`]; `build` clamps them instead.
     pub fn bleed(mut self, value: f32) -> Self {
         self.bleed = value;
         self
     }
 
-    /// Build the `ChordDetector`
+    /// Set where each bin's bleed is subtracted, in semitones from the bin
+    /// (-11..=11, default -5: a perfect fourth down).
+    ///
+    /// Every bin `i` removes `bleed` times its energy from bin `i +
+    /// interval`. Offsets act on pitch classes, so they wrap: -5 and +7
+    /// name the same bin, as do +5 and -7. Use +5 (a fourth up, i.e. a
+    /// fifth down) to suppress energy that strong fifth overtones leave a
+    /// fifth above the sounding note. `0` scales every bin down by the same
+    /// factor, which ranks chords as if there were no directional bleed.
+    pub fn bleed_interval(mut self, semitones: i8) -> Self {
+        self.bleed_interval = semitones;
+        self
+    }
+
+    /// Set a separate bleed suppression factor used only when scoring
+    /// seventh chords (0.0..=1.0, validated like `bleed`).
+    ///
+    /// Four-note chords put an extra set of overtones into the chromagram,
+    /// and the third harmonic of each tone lands a fifth above it (G → D in
+    /// C7), where it counts against the seventh profile. Chord tones
+    /// themselves are never penalised, so this factor controls how much of
+    /// that stray overtone energy is left to count against a seventh:
+    /// values above `bleed` favour sevenths over triads, values below favour
+    /// triads. Triads are always scored with `bleed`. Defaults to `bleed`.
+    pub fn seventh_bleed(mut self, value: f32) -> Self {
+        self.seventh_bleed = Some(value);
+        self
+    }
+
+    /// Take the square root of every profile score while ranking.
+    ///
+    /// Ranking only depends on relative order, so by default profiles are
+    /// compared on their squared residual and the root is taken once for each
+    /// returned chord. Reported confidences are identical either way; enable
+    /// this only if the intermediate per-profile scores must be exact.
+    pub fn exact_confidence(mut self, exact: bool) -> Self {
+        self.exact_confidence = exact;
+        self
+    }
+
+    /// Set the chroma energy (sum of squared bins, before bleed suppression)
+    /// below which a frame counts as silent. Default 1e-4; must be finite
+    /// and non-negative.
+    ///
+    /// Silent frames yield `None` from [`ChordDetector::detect_chord_opt`].
+    /// The energy is measured on the raw input, so `bleed` never turns a
+    /// quiet but tonal frame into silence.
+    pub fn silence_threshold(mut self, threshold: f32) -> Self {
+        self.silence_threshold = threshold;
+        self
+    }
+
+    /// Forget streaming history after `frames` consecutive silent frames
+    /// (default 0 = never).
+    ///
+    /// Every detection call counts towards the run; a frame is silent when
+    /// its energy is below `silence_threshold`. Once the run reaches `frames`
+    /// the detector behaves as if [`ChordDetector::reset`] had been called, so
+    /// a chord heard before a pause cannot bias the first chord after it.
+    pub fn silence_reset_frames(mut self, frames: usize) -> Self {
+        self.silence_reset_frames = frames;
+        self
+    }
+
+    /// Penalise chord tones that are absent, forgiving up to `tones` of them.
+    ///
+    /// The default score only measures energy *outside* each template, so
+    /// absent chord tones cost nothing. Setting a tolerance adds a
+    /// completeness term: a chord tone quieter than 10% of the loudest bin
+    /// counts as missing, and every missing tone beyond the first `tones`
+    /// costs as much as a full-strength note outside the chord. `0` demands
+    /// complete voicings; `1` lets a muted string (e.g. a C major without its
+    /// fifth) keep its label. Not set by default.
+    pub fn missing_note_tolerance(mut self, tones: usize) -> Self {
+        self.missing_note_tolerance = Some(tones);
+        self
+    }
+
+    /// Accept seventh chords voiced without their root (default false).
+    ///
+    /// Jazz comping often leaves the root to the bass player, so a Cmaj7
+    /// may sound as just E-G-B. Scores only count energy outside each
+    /// template, so such a voicing already matches Cmaj7 as well as it
+    /// matches E minor; what rejects it is `missing_note_tolerance`, which
+    /// counts the absent root as a missing tone. With this enabled the root
+    /// of a seventh chord is never counted as missing, while a missing
+    /// third, fifth or seventh still is. The price is ambiguity: a rootless
+    /// seventh has the same pitch classes as a triad (E-G-B is both Em and
+    /// a rootless Cmaj7, E-G-Bb both Edim and a rootless C7), and on a tie
+    /// the triad is ranked first, so look at `top_k` rather than the best
+    /// chord alone. Without `missing_note_tolerance` this implies a
+    /// tolerance of 0, so every other missing tone is penalised.
+    pub fn allow_rootless(mut self, allow: bool) -> Self {
+        self.allow_rootless = allow;
+        self
+    }
+
+    /// Favour the reading whose root is the loudest chord tone (0.0..=1.0,
+    /// default 0.0 = off).
+    ///
+    /// Chords with the same pitch classes, such as Csus2 and Gsus4, score
+    /// identically because every chord tone counts the same. With a weight
+    /// `w` each hypothesis also pays
+    /// `w * (1 - root / loudest) * loudest^2`, normalized like the rest of
+    /// its score, where `root` is the energy at its root and `loudest` that
+    /// of its strongest chord tone. A hypothesis whose root is the loudest
+    /// tone, usually because it is doubled or sits in the bass, pays
+    /// nothing; at `w = 1.0` one whose root is silent pays as much as a
+    /// full-strength note outside the chord. Since a chromagram carries no
+    /// octave information this cannot tell a true bass from a loud upper
+    /// voice, so it helps most on voicings that double the root.
+    pub fn weight_bass(mut self, weight: f32) -> Self {
+        self.weight_bass = weight;
+        self
+    }
+
+    /// Report an unknown chord from `detect_chord` when even the best
+    /// match explains less than `quality` of the chroma energy.
+    ///
+    /// Match quality is the fraction (0.0..=1.0) of the bleed-suppressed
+    /// chroma energy that falls on the best chord's tones: 1.0 means nothing
+    /// sounds outside the chord, while broadband noise scores little more
+    /// than the chord's share of the 12 bins (about 0.3 for a triad). Below
+    /// the threshold `detect_chord` returns a chord whose `root` is
+    /// `NoteName::Unknown` and `quality` is `ChordKind::Unknown`, keeping the
+    /// best candidate's confidence. `top_k` is unaffected. Must be in
+    /// 0.0..=1.0; not set by default.
+    pub fn min_match_quality(mut self, quality: f32) -> Self {
+        self.min_match_quality = Some(quality);
+        self
+    }
+
+    /// Suppress detections from `detect_chord_gated` whose confidence is
+    /// below `confidence` (0.0..=1.0).
+    ///
+    /// The gate always uses the normalized similarity described under
+    /// [`ChordDetectorBuilder::normalized_confidence`], where higher is
+    /// better and 1.0 is a perfect match, whether or not confidences are
+    /// reported that way. Other detection methods are unaffected. Not set by
+    /// default.
+    pub fn min_confidence(mut self, confidence: f32) -> Self {
+        self.min_confidence = Some(confidence);
+        self
+    }
+
+    /// Report confidences as a loudness-independent similarity in
+    /// 0.0..=1.0 where **higher is better** (default false).
+    ///
+    /// The raw residual is measured against the energy of the (bleed
+    /// suppressed) input, as if the chroma had been scaled to unit energy
+    /// first: `1 - residual / energy`. A chroma with nothing outside the
+    /// template scores 1.0 at any level, and silence scores 0.0. Missing-tone
+    /// penalties lower the similarity too. Applies to every confidence the
+    /// detector reports (`Chord::confidence`, `is_chord`, `ranked_grid`,
+    /// `root_confidence`); ranking is unchanged. When disabled the raw
+    /// lower-is-better scores are reported as before.
+    pub fn normalized_confidence(mut self, normalized: bool) -> Self {
+        self.normalized_confidence = normalized;
+        self
+    }
+
+    /// Replace the built-in chord qualities with user-defined templates.
+    ///
+    /// Every template is scored on all 12 roots. Detected chords report
+    /// `ChordKind::Custom(i)`, where `i` is the template's position in
+    /// `templates`; look the name up with [`ChordDetector::template`] or
+    /// [`ChordDetector::chord_name`]. Custom chords are never treated as
+    /// sevenths by `seventh_bleed`. An empty list keeps the built-in set.
+    /// At most 65536 templates are accepted, each with a positive bias and
+    /// between one and eleven distinct pitch classes.
+    pub fn templates(mut self, templates: Vec<ChordTemplate>) -> Self {
+        self.templates = templates;
+        self
+    }
+
+    /// Score only the given built-in qualities (default: all twelve).
+    ///
+    /// Restricting the search to the chords the material actually uses,
+    /// e.g. `[Major, Minor, DominantSeventh]` for simple pop, removes
+    /// whole classes of false positives. Every detection method, including
+    /// `top_k` and `score_all`, then covers only these qualities, in their
+    /// usual order. The list must be non-empty, contain only built-in
+    /// qualities and cannot be combined with `templates`; otherwise
+    /// [`ChordDetectorBuilder::try_build`] returns an error.
+    pub fn qualities(mut self, qualities: &[ChordKind]) -> Self {
+        self.qualities = Some(qualities.to_vec());
+        self
+    }
+
+    /// Set how chord profiles weight each pitch class (default `Binary`).
+    ///
+    /// `Harmonic` credits the pitch classes of each chord tone's first
+    /// few partials, with weight falling by 0.7 per partial, and rescales
+    /// the normalizer to the remaining off-chord weight. Real instruments
+    /// put energy there, which the binary model scores as a full miss.
+    /// Applies to built-in qualities and custom templates alike.
+    pub fn template_model(mut self, model: TemplateModel) -> Self {
+        self.template_model = model;
+        self
+    }
+
+    /// Guess the bass note of every detected chord (default false).
+    ///
+    /// When enabled, `Chord::bass` is set to the loudest chord tone in the
+    /// input chroma, so a first-inversion C major with a strong E reads as
+    /// `C/E`; root position reports the root itself. This is only a
+    /// heuristic: a chromagram has no octave information, so a loud upper
+    /// voice is indistinguishable from a bass note, and doubled tones (such
+    /// as the root in most guitar voicings) look like bass notes. Treat the
+    /// result as a hint, ideally confirmed by a low-passed chroma. When
+    /// disabled `bass` stays `None`.
+    pub fn detect_bass(mut self, detect: bool) -> Self {
+        self.detect_bass = detect;
+        self
+    }
+
+    /// Number of recent detections [`ChordDetector::detect_chord_smoothed`]
+    /// votes over (default 1 = no smoothing; 0 is treated as 1).
+    pub fn smoothing_window(mut self, frames: usize) -> Self {
+        self.smoothing_window = frames.max(1);
+        self
+    }
+
+    /// Weight of each new frame in the moving average of
+    /// [`ChordDetector::detect_chord_weighted`] (0.0 < alpha <= 1.0,
+    /// default 1.0 = no smoothing).
+    ///
+    /// Every hypothesis keeps `alpha * new + (1 - alpha) * previous`, so a
+    /// frame's influence decays by a factor of `1 - alpha` per later frame
+    /// and fades to a tenth after about `2.3 / alpha` frames.
+    pub fn smoothing_alpha(mut self, alpha: f32) -> Self {
+        self.smoothing_alpha = alpha;
+        self
+    }
+
+    /// Build the `ChordDetector`, repairing an invalid configuration.
+    ///
+    /// Never fails: every setting [`ChordDetectorBuilder::try_build`] would
+    /// reject is replaced first. Out-of-range factors (`bleed`,
+    /// `seventh_bleed`, `min_match_quality`, `min_confidence`,
+    /// `weight_bass`) and `bleed_interval` are clamped into range. NaN
+    /// factors, a negative or non-finite `silence_threshold` and a
+    /// `smoothing_alpha` outside (0.0, 1.0] fall back to their defaults.
+    /// Invalid `qualities` (or ones combined with `templates`) are ignored,
+    /// and so are invalid `templates`, keeping the built-in set. Use
+    /// `try_build` to be told about such settings instead.
     pub fn build(self) -> ChordDetector {
-        ChordDetector::with_bleed(self.bleed)
+        match self.repaired().try_build() {
+            Ok(detector) => detector,
+            Err(e) => unreachable!("repaired configuration was rejected: {e}"),
+        }
+    }
+
+    /// Replace every setting `try_build` would reject, as described under
+    /// [`ChordDetectorBuilder::build`]
+    fn repaired(mut self) -> Self {
+        let unit = |v: f32| (!v.is_nan()).then(|| v.clamp(0.0, 1.0));
+        self.bleed = unit(self.bleed).unwrap_or(DEFAULT_BLEED);
+        self.seventh_bleed = self.seventh_bleed.and_then(unit);
+        self.min_match_quality = self.min_match_quality.and_then(unit);
+        self.min_confidence = self.min_confidence.and_then(unit);
+        self.weight_bass = unit(self.weight_bass).unwrap_or(0.0);
+        self.bleed_interval = self.bleed_interval.clamp(-11, 11);
+        if !(self.silence_threshold.is_finite() && self.silence_threshold >= 0.0) {
+            self.silence_threshold = DEFAULT_SILENCE_THRESHOLD;
+        }
+        if !(self.smoothing_alpha > 0.0 && self.smoothing_alpha <= 1.0) {
+            self.smoothing_alpha = 1.0;
+        }
+        if check_templates(&self.templates).is_err() {
+            self.templates.clear();
+        }
+        let valid_qualities = |q: &Vec<ChordKind>| {
+            !q.is_empty() && q.iter().all(|k| CHORD_KINDS.contains(k))
+        };
+        if !self.templates.is_empty() || !self.qualities.as_ref().is_none_or(valid_qualities) {
+            self.qualities = None;
+        }
+        self
+    }
+
+    /// Build the `ChordDetector`, validating the configuration.
+    ///
+    /// Returns `Err(ChordError::InvalidArgument)` if `bleed`,
+    /// `seventh_bleed`, `min_match_quality`, `min_confidence` or
+    /// `weight_bass` is outside 0.0..=1.0 or not finite, if
+    /// `bleed_interval` is outside -11..=11, or if `qualities` is empty,
+    /// contains `Unknown` or `Custom` kinds, or is combined with
+    /// `templates`, or if `smoothing_alpha` is outside (0.0, 1.0], if
+    /// `silence_threshold` is negative or not finite, or if a custom
+    /// template is invalid (see [`ChordDetectorBuilder::templates`]).
+    pub fn try_build(self) -> Result<ChordDetector, ChordError> {
+        check_unit_range("bleed", self.bleed)?;
+        if let Some(seventh_bleed) = self.seventh_bleed {
+            check_unit_range("seventh_bleed", seventh_bleed)?;
+        }
+        if !(self.smoothing_alpha > 0.0 && self.smoothing_alpha <= 1.0) {
+            return Err(ChordError::InvalidArgument {
+                arg: "smoothing_alpha",
+                msg: format!("must be in (0.0, 1.0], got {}", self.smoothing_alpha),
+            });
+        }
+        if let Some(min_match_quality) = self.min_match_quality {
+            check_unit_range("min_match_quality", min_match_quality)?;
+        }
+        if let Some(min_confidence) = self.min_confidence {
+            check_unit_range("min_confidence", min_confidence)?;
+        }
+        check_unit_range("weight_bass", self.weight_bass)?;
+        if !(self.silence_threshold.is_finite() && self.silence_threshold >= 0.0) {
+            return Err(ChordError::InvalidArgument {
+                arg: "silence_threshold",
+                msg: format!("must be finite and >= 0.0, got {}", self.silence_threshold),
+            });
+        }
+        if !(-11..=11).contains(&self.bleed_interval) {
+            return Err(ChordError::InvalidArgument {
+                arg: "bleed_interval",
+                msg: format!("must be in -11..=11, got {}", self.bleed_interval),
+            });
+        }
+        let mut detector = match (&self.qualities, self.templates.is_empty()) {
+            (None, true) => {
+                ChordDetector::with_kinds(self.bleed, &CHORD_KINDS, self.template_model)
+            }
+            (None, false) => {
+                check_templates(&self.templates)?;
+                ChordDetector::with_templates(self.bleed, self.templates, self.template_model)
+            }
+            (Some(_), false) => {
+                return Err(ChordError::InvalidArgument {
+                    arg: "qualities",
+                    msg: "cannot be combined with templates".to_string(),
+                });
+            }
+            (Some(qualities), true) => {
+                if qualities.is_empty() {
+                    return Err(ChordError::InvalidArgument {
+                        arg: "qualities",
+                        msg: "must select at least one quality".to_string(),
+                    });
+                }
+                if let Some(bad) = qualities.iter().find(|q| !CHORD_KINDS.contains(q)) {
+                    return Err(ChordError::InvalidArgument {
+                        arg: "qualities",
+                        msg: format!("{bad:?} is not a built-in quality"),
+                    });
+                }
+                ChordDetector::with_kinds(self.bleed, qualities, self.template_model)
+            }
+        };
+        detector.bleed_interval = self.bleed_interval;
+        detector.bleed_offset = bleed_offset(self.bleed_interval);
+        detector.seventh_bleed = self.seventh_bleed;
+        detector.exact_confidence = self.exact_confidence;
+        detector.silence_threshold = self.silence_threshold;
+        detector.silence_reset_frames = self.silence_reset_frames;
+        detector.missing_note_tolerance = self.missing_note_tolerance;
+        detector.allow_rootless = self.allow_rootless;
+        detector.weight_bass = self.weight_bass;
+        detector.min_match_quality = self.min_match_quality;
+        detector.min_confidence = self.min_confidence;
+        detector.normalized_confidence = self.normalized_confidence;
+        detector.detect_bass = self.detect_bass;
+        detector.smoothing_window = self.smoothing_window;
+        detector.smoothing_alpha = self.smoothing_alpha;
+        detector.template_model = self.template_model;
+        detector.history = VecDeque::with_capacity(self.smoothing_window);
+        Ok(detector)
     }
 }
 
@@ -213,11 +1251,41 @@ impl Default for ChordDetectorBuilder {
 }
 
 /// Main chord detector
+///
+/// Cloning copies the precomputed profiles and the streaming history
+/// (smoothing window, silence run) along with the configuration.
+#[derive(Clone)]
 pub struct ChordDetector {
     bleed: f32,
+    bleed_interval: i8,
+    bleed_offset: usize,
+    seventh_bleed: Option<f32>,
+    exact_confidence: bool,
+    silence_threshold: f32,
+    silence_reset_frames: usize,
+    missing_note_tolerance: Option<usize>,
+    allow_rootless: bool,
+    weight_bass: f32,
+    min_match_quality: Option<f32>,
+    min_confidence: Option<f32>,
+    normalized_confidence: bool,
+    detect_bass: bool,
+    smoothing_window: usize,
+    smoothing_alpha: f32,
+    template_model: TemplateModel,
+    history: VecDeque<Chord>,
+    /// Moving average of every hypothesis' energy-normalized score; empty
+    /// until the first weighted detection
+    smoothed_scores: Vec<f32>,
+    silent_frames: usize,
+    input_work: Chromagram,
     chroma_work: Chromagram,
-    profiles: [PrecalcProfile; NUM_CHORDS],
-    scores: [f32; NUM_CHORDS],
+    seventh_work: Chromagram,
+    kinds: Vec<ChordKind>,
+    templates: Vec<ChordTemplate>,
+    profiles: Vec<PrecalcProfile>,
+    scores: Vec<f32>,
+    confidences: Vec<f32>,
     idx_scores: Vec<(usize, f32)>,
 }
 
@@ -229,87 +1297,723 @@ impl ChordDetector {
 
     /// Create a detector with default bleed = 0.157
     pub fn new() -> Self {
-        Self::with_bleed(0.157)
+        Self::with_bleed(DEFAULT_BLEED)
     }
 
     /// Create a detector with a custom bleed suppression factor
     fn with_bleed(bleed: f32) -> Self {
+        Self::with_kinds(bleed, &CHORD_KINDS, TemplateModel::Binary)
+    }
+
+    /// Create a detector scoring only the built-in qualities in `selected`,
+    /// kept in `CHORD_KINDS` order
+    fn with_kinds(bleed: f32, selected: &[ChordKind], model: TemplateModel) -> Self {
         // Precompute profiles
-        let mut profiles = [PrecalcProfile {
-            weights: [0.0; SEMITONES],
-            inv_norm: 0.0,
-        }; NUM_CHORDS];
-
-        for (kind_idx, &(_kind, bias, interval_count)) in CHORD_SPECS.iter().enumerate() {
-            let inv = 1.0 / (((SEMITONES as f32) - interval_count) * bias);
-            let intervals = CHORD_INTERVALS[kind_idx];
-            for root in 0..SEMITONES {
-                let base = kind_idx * SEMITONES + root;
-                profiles[base].inv_norm = inv;
-                for &off in intervals {
-                    let note = (root + off) % SEMITONES;
-                    profiles[base].weights[note] = 1.0;
-                }
+        let mut profiles = Vec::with_capacity(NUM_CHORDS);
+        let mut kinds = Vec::with_capacity(NUM_CHORD_KINDS);
+        for (kind_idx, &(kind, bias, interval_count)) in CHORD_SPECS.iter().enumerate() {
+            if selected.contains(&kind) {
+                let intervals = CHORD_INTERVALS[kind_idx];
+                push_profiles(&mut profiles, intervals, interval_count, bias, model);
+                kinds.push(kind);
             }
         }
+        Self::with_profiles(bleed, kinds, Vec::new(), profiles)
+    }
+
+    /// Create a detector scoring user-defined templates instead of the
+    /// built-in qualities
+    fn with_templates(bleed: f32, templates: Vec<ChordTemplate>, model: TemplateModel) -> Self {
+        let mut profiles = Vec::with_capacity(templates.len() * SEMITONES);
+        for t in &templates {
+            let mask = t.intervals.iter().fold(0u16, |m, &off| m | 1 << (off % SEMITONES));
+            let offsets: Vec<usize> = (0..SEMITONES).filter(|&i| mask & 1 << i != 0).collect();
+            push_profiles(&mut profiles, &offsets, offsets.len() as f32, t.bias, model);
+        }
+        let kinds = (0..templates.len()).map(|i| ChordKind::Custom(i as u16)).collect();
+        Self::with_profiles(bleed, kinds, templates, profiles)
+    }
 
+    fn with_profiles(
+        bleed: f32,
+        kinds: Vec<ChordKind>,
+        templates: Vec<ChordTemplate>,
+        profiles: Vec<PrecalcProfile>,
+    ) -> Self {
+        let num_chords = profiles.len();
         ChordDetector {
             bleed,
+            bleed_interval: DEFAULT_BLEED_INTERVAL,
+            bleed_offset: bleed_offset(DEFAULT_BLEED_INTERVAL),
+            seventh_bleed: None,
+            exact_confidence: false,
+            silence_threshold: DEFAULT_SILENCE_THRESHOLD,
+            silence_reset_frames: 0,
+            missing_note_tolerance: None,
+            allow_rootless: false,
+            weight_bass: 0.0,
+            min_match_quality: None,
+            min_confidence: None,
+            normalized_confidence: false,
+            detect_bass: false,
+            smoothing_window: 1,
+            smoothing_alpha: 1.0,
+            template_model: TemplateModel::Binary,
+            history: VecDeque::new(),
+            smoothed_scores: Vec::new(),
+            silent_frames: 0,
+            input_work: [0.0; SEMITONES],
             chroma_work: [0.0; SEMITONES],
+            seventh_work: [0.0; SEMITONES],
+            kinds,
+            templates,
             profiles,
-            scores: [0.0; NUM_CHORDS],
-            idx_scores: Vec::with_capacity(NUM_CHORDS),
+            scores: vec![0.0; num_chords],
+            confidences: vec![0.0; num_chords],
+            idx_scores: Vec::with_capacity(num_chords),
+        }
+    }
+
+    /// Rebuild a detector from a configuration taken with
+    /// [`ChordDetector::config`] (or written by hand).
+    ///
+    /// Invalid settings in a hand-written configuration are repaired like
+    /// in [`ChordDetectorBuilder::build`].
+    pub fn from_config(config: DetectorConfig) -> Self {
+        ChordDetectorBuilder {
+            bleed: config.bleed,
+            bleed_interval: config.bleed_interval,
+            seventh_bleed: config.seventh_bleed,
+            exact_confidence: config.exact_confidence,
+            silence_threshold: config.silence_threshold,
+            silence_reset_frames: config.silence_reset_frames,
+            missing_note_tolerance: config.missing_note_tolerance,
+            allow_rootless: config.allow_rootless,
+            weight_bass: config.weight_bass,
+            min_match_quality: config.min_match_quality,
+            min_confidence: config.min_confidence,
+            normalized_confidence: config.normalized_confidence,
+            templates: config.templates,
+            qualities: config.qualities,
+            template_model: config.template_model,
+            detect_bass: config.detect_bass,
+            smoothing_window: config.smoothing_window.max(1),
+            smoothing_alpha: config.smoothing_alpha,
+        }
+        .build()
+    }
+
+    /// The configuration this detector was built with.
+    ///
+    /// [`ChordDetector::from_config`] turns it back into a detector that
+    /// produces identical detections, starting from empty history.
+    pub fn config(&self) -> DetectorConfig {
+        let all_builtin = self.templates.is_empty() && self.kinds[..] == CHORD_KINDS[..];
+        DetectorConfig {
+            bleed: self.bleed,
+            bleed_interval: self.bleed_interval,
+            seventh_bleed: self.seventh_bleed,
+            exact_confidence: self.exact_confidence,
+            silence_threshold: self.silence_threshold,
+            silence_reset_frames: self.silence_reset_frames,
+            missing_note_tolerance: self.missing_note_tolerance,
+            allow_rootless: self.allow_rootless,
+            weight_bass: self.weight_bass,
+            min_match_quality: self.min_match_quality,
+            min_confidence: self.min_confidence,
+            normalized_confidence: self.normalized_confidence,
+            templates: self.templates.clone(),
+            qualities: (self.templates.is_empty() && !all_builtin).then(|| self.kinds.clone()),
+            template_model: self.template_model,
+            detect_bass: self.detect_bass,
+            smoothing_window: self.smoothing_window,
+            smoothing_alpha: self.smoothing_alpha,
+        }
+    }
+
+    /// The user-defined template behind a `ChordKind::Custom` quality.
+    ///
+    /// Returns `None` for built-in qualities and for indices this detector
+    /// does not have.
+    pub fn template(&self, quality: ChordKind) -> Option<&ChordTemplate> {
+        match quality {
+            ChordKind::Custom(idx) => self.templates.get(idx as usize),
+            _ => None,
+        }
+    }
+
+    /// Chord symbol for `chord`, using template names for custom chords.
+    ///
+    /// Same as `chord.to_string()` for built-in qualities; a custom chord
+    /// reads as its root followed by the template name (`"Cadd9"`).
+    pub fn chord_name(&self, chord: &Chord) -> String {
+        match (self.template(chord.quality), chord.root) {
+            (Some(t), root) if root != NoteName::Unknown => format!("{}{}", root.symbol(), t.name),
+            _ => chord.to_string(),
         }
     }
 
     /// Detect the single best chord from a chromagram slice.
     ///
-    /// Returns `Err(ChordError::InvalidLength)` if `chroma.len() != SEMITONES`.
+    /// With `min_match_quality` set, a poorly matching best chord is reported
+    /// with unknown root and quality instead. So is a silent frame, whose
+    /// energy (sum of squared bins) is below `silence_threshold` (default
+    /// 1e-4): its confidence is the worst possible, infinity (or 0.0 with
+    /// `normalized_confidence`), so silence never reads as a perfect match.
+    ///
+    /// Returns `Err(ChordError::InvalidLength)` if `chroma.len() != SEMITONES`,
+    /// or `Err(ChordError::NonFinite)` if a bin is NaN or infinite.
     pub fn detect_chord(&mut self, chroma: &[f32]) -> Result<Chord, ChordError> {
-        let mut list = self.top_k(chroma, 1)?;
-        Ok(list.remove(0))
+        let best = self.top_k(chroma, 1)?.remove(0);
+        match self.min_match_quality {
+            Some(min) if self.match_quality(&best) < min => Ok(Chord {
+                root: NoteName::Unknown,
+                quality: ChordKind::Unknown,
+                bass: None,
+                ..best
+            }),
+            _ => Ok(best),
+        }
+    }
+
+    /// Detect the best chord together with how far it stands out from all
+    /// hypotheses.
+    ///
+    /// The extra value is the peakedness `1 - best / mean`, where `best` is
+    /// the lowest squared residual (scores are distances, lower is better)
+    /// and `mean` the average squared residual over every root and quality,
+    /// whatever `exact_confidence` is set to. It lies in
+    /// 0.0..=1.0 and, unlike `Chord::confidence`, does not change with the
+    /// loudness of the frame: 1.0 is an exact match standing out from the
+    /// rest, values near 0.0 an ambiguous frame where every hypothesis fits
+    /// about as well. A silent frame, or one where every score is zero,
+    /// gives 0.0. The chord is exactly what `detect_chord` reports.
+    ///
+    /// Returns `Err(ChordError::InvalidLength)` if `chroma.len() != SEMITONES`,
+    /// or `Err(ChordError::NonFinite)` if a bin is NaN or infinite.
+    pub fn detect_chord_full(&mut self, chroma: &[f32]) -> Result<(Chord, f32), ChordError> {
+        let chord = self.detect_chord(chroma)?;
+        let squared = |s: f32| if self.exact_confidence { s * s } else { s };
+        let mean = self.scores.iter().map(|&s| squared(s)).sum::<f32>() / self.scores.len() as f32;
+        if self.work_is_silent() || mean <= 0.0 {
+            return Ok((chord, 0.0));
+        }
+        let best = self.scores.iter().map(|&s| squared(s)).fold(f32::INFINITY, f32::min);
+        Ok((chord, (1.0 - best / mean).clamp(0.0, 1.0)))
+    }
+
+    /// Detect the best and second-best chords and how far apart they score.
+    ///
+    /// Returns `(best, runner_up, margin)`, where `margin` is the absolute
+    /// difference of their confidences, on the same scale as
+    /// `Chord::confidence` (a similarity gap with `normalized_confidence`).
+    /// A larger margin means a less ambiguous detection; 0.0 is a tie.
+    /// Like `top_k`, `min_match_quality` is not applied. A silent frame
+    /// returns the unknown chord twice with a margin of 0.0.
+    ///
+    /// Returns `Err(ChordError::InvalidLength)` if `chroma.len() != SEMITONES`,
+    /// or `Err(ChordError::NonFinite)` if a bin is NaN or infinite.
+    pub fn detect_with_margin(
+        &mut self,
+        chroma: &[f32],
+    ) -> Result<(Chord, Chord, f32), ChordError> {
+        let ranked = self.top_k(chroma, 2)?;
+        let (best, runner_up) = match ranked[..] {
+            [best, runner_up] => (best, runner_up),
+            _ => return Ok((ranked[0], ranked[0], 0.0)),
+        };
+        Ok((best, runner_up, (runner_up.confidence - best.confidence).abs()))
+    }
+
+    /// Detect on `chroma` transposed up by `semitones` (down if negative),
+    /// then move the detected root and bass back down by the same amount.
+    ///
+    /// The chord is reported in the pitch frame of `chroma`, so a pipeline
+    /// that normalizes every frame to a common key (for key-invariant
+    /// features) can still label chords in the original key. Streaming
+    /// history, such as `smoothing_window` votes, is kept in the transposed
+    /// frame. Errors as [`ChordDetector::detect_chord`].
+    pub fn detect_chord_transposed(
+        &mut self,
+        chroma: &[f32],
+        semitones: i32,
+    ) -> Result<Chord, ChordError> {
+        check_chroma(chroma)?;
+        let mut rotated = [0.0; SEMITONES];
+        rotated.copy_from_slice(chroma);
+        rotated.rotate_right(semitones.rem_euclid(SEMITONES as i32) as usize);
+
+        let chord = self.detect_chord(&rotated)?;
+        Ok(Chord {
+            root: chord.root.transpose(-semitones),
+            bass: chord.bass.map(|bass| bass.transpose(-semitones)),
+            ..chord
+        })
+    }
+
+    /// Detect the best chord and report how well each of its tones is
+    /// present.
+    ///
+    /// Lets a tutor say which chord tone is weak ("the G is missing") and
+    /// what was played besides. The energies come from the bleed-suppressed
+    /// working chroma, not from `chroma` itself; see [`ToneFit`]. An unknown
+    /// chord (silence, or a match rejected by `min_match_quality`) has no
+    /// tones and no extra. Errors as [`ChordDetector::detect_chord`].
+    pub fn tone_fit(&mut self, chroma: &[f32]) -> Result<ToneFit, ChordError> {
+        let chord = self.detect_chord(chroma)?;
+        let Some(idx) = self.chord_index(chord.root, chord.quality) else {
+            return Ok(ToneFit {
+                chord,
+                tones: Vec::new(),
+                extra: None,
+            });
+        };
+        let work = match self.seventh_bleed {
+            Some(_) if chord.quality.is_seventh() => &self.seventh_work,
+            _ => &self.chroma_work,
+        };
+        let weights = &self.profiles[idx].weights;
+        let root = idx % SEMITONES;
+
+        let mut tones = Vec::new();
+        let mut extra: Option<(NoteName, f32)> = None;
+        for off in 0..SEMITONES {
+            let i = (root + off) % SEMITONES;
+            let note = NoteName::from_idx(i);
+            if weights[i] >= 1.0 {
+                tones.push((note, work[i]));
+            } else if work[i] > extra.map_or(0.0, |(_, energy)| energy) {
+                extra = Some((note, work[i]));
+            }
+        }
+        Ok(ToneFit {
+            chord,
+            tones,
+            extra,
+        })
+    }
+
+    /// Detect a chord for every chromagram in `chromas`, in order.
+    ///
+    /// Equivalent to calling [`ChordDetector::detect_chord`] on each frame,
+    /// so streaming state (silence tracking) carries from one to the next.
+    /// Stops at the first frame that fails to detect.
+    pub fn detect_sequence(
+        &mut self,
+        chromas: &[[f32; SEMITONES]],
+    ) -> Result<Vec<Chord>, ChordError> {
+        chromas.iter().map(|chroma| self.detect_chord(chroma)).collect()
+    }
+
+    /// Detect a chord for every chromagram and merge runs of the same chord.
+    ///
+    /// Each entry is `(chord, start, end)`: frames `start..end` (end
+    /// exclusive) all detected the same root and quality. Confidence and
+    /// bass are ignored when comparing, and the reported chord is the run's
+    /// first detection.
+    pub fn detect_segments(
+        &mut self,
+        chromas: &[[f32; SEMITONES]],
+    ) -> Result<Vec<(Chord, usize, usize)>, ChordError> {
+        let mut segments: Vec<(Chord, usize, usize)> = Vec::new();
+        for (i, chord) in self.detect_sequence(chromas)?.into_iter().enumerate() {
+            match segments.last_mut() {
+                Some((run, _, end)) if (run.root, run.quality) == (chord.root, chord.quality) => {
+                    *end = i + 1;
+                }
+                _ => segments.push((chord, i, i + 1)),
+            }
+        }
+        Ok(segments)
+    }
+
+    /// Detect the best chord, then report the most common chord among the
+    /// last `smoothing_window` detections.
+    ///
+    /// Smoothing votes on labels rather than averaging chroma: every call
+    /// detects the current frame as `detect_chord` would and adds it to the
+    /// history, and the root and quality seen most often in the window win
+    /// (ties go to the most recent). The returned chord is the latest
+    /// detection with that label, so its confidence and bass are current.
+    /// A single stray frame therefore never changes the output, while a
+    /// real change takes over once it fills half the window. History is
+    /// cleared by `reset` and by `silence_reset_frames`.
+    ///
+    /// Returns `Err(ChordError::InvalidLength)` if `chroma.len() != SEMITONES`,
+    /// or `Err(ChordError::NonFinite)` if a bin is NaN or infinite.
+    pub fn detect_chord_smoothed(&mut self, chroma: &[f32]) -> Result<Chord, ChordError> {
+        let chord = self.detect_chord(chroma)?;
+        if self.history.len() == self.smoothing_window {
+            self.history.pop_front();
+        }
+        self.history.push_back(chord);
+
+        let same = |a: &Chord, b: &Chord| a.root == b.root && a.quality == b.quality;
+        let mut best = (0, chord);
+        for candidate in self.history.iter().rev() {
+            let votes = self.history.iter().filter(|c| same(c, candidate)).count();
+            if votes > best.0 {
+                best = (votes, *candidate);
+            }
+        }
+        Ok(best.1)
+    }
+
+    /// Detect the chord with the best confidence averaged over recent
+    /// frames.
+    ///
+    /// Where `detect_chord_smoothed` votes on each frame's winner, this keeps
+    /// an exponential moving average of every hypothesis' score with weight
+    /// `smoothing_alpha` on the newest frame, and reports the hypothesis
+    /// with the best average. Scores are divided by the frame's energy
+    /// before averaging, as if every chroma had unit energy, so loud frames
+    /// do not outweigh quiet ones; within one frame they rank exactly as in
+    /// `detect_chord`. A chord that narrowly loses a few frames therefore
+    /// keeps its lead, and a new chord takes over once its accumulated
+    /// evidence overtakes the old one's decaying average. The first frame
+    /// seeds the average; with the default `alpha` of 1.0 only the current
+    /// frame counts, as in `detect_chord`.
+    ///
+    /// The returned chord carries the current frame's confidence and bass
+    /// for that root and quality, on the usual scale. `min_match_quality` is
+    /// not applied. A silent frame returns the unknown chord and leaves the
+    /// average untouched; the average is cleared by `reset` and by
+    /// `silence_reset_frames`.
+    ///
+    /// Returns `Err(ChordError::InvalidLength)` if `chroma.len() != SEMITONES`,
+    /// or `Err(ChordError::NonFinite)` if a bin is NaN or infinite.
+    pub fn detect_chord_weighted(&mut self, chroma: &[f32]) -> Result<Chord, ChordError> {
+        check_chroma(chroma)?;
+        self.chroma_work.copy_from_slice(chroma);
+        self.score_work();
+        let energy: f32 = self.input_work.iter().map(|c| c * c).sum();
+        if energy < self.silence_threshold {
+            return Ok(self.silent_chord());
+        }
+
+        let seeded = !self.smoothed_scores.is_empty();
+        self.smoothed_scores.resize(self.scores.len(), 0.0);
+        let alpha = self.smoothing_alpha;
+        let mut best = 0;
+        for (idx, &score) in self.scores.iter().enumerate() {
+            let squared = if self.exact_confidence { score * score } else { score };
+            let normalized = squared / energy;
+            let smoothed = &mut self.smoothed_scores[idx];
+            *smoothed = if seeded {
+                alpha * normalized + (1.0 - alpha) * *smoothed
+            } else {
+                normalized
+            };
+            // Ties go to the earlier quality, as in `detect_chord`
+            if *smoothed < self.smoothed_scores[best] {
+                best = idx;
+            }
+        }
+
+        Ok(Chord {
+            root: NoteName::from_idx(best % SEMITONES),
+            quality: self.kinds[best / SEMITONES],
+            confidence: self.confidence_of(best, self.scores[best]),
+            bass: self.detect_bass.then(|| self.loudest_tone(best)),
+        })
+    }
+
+    /// Detect the best chord, or `None` for a silent frame.
+    ///
+    /// A frame is silent when its energy (sum of squared bins) is below
+    /// `silence_threshold`; silent frames still count towards
+    /// `silence_reset_frames`. Otherwise behaves like `detect_chord`.
+    ///
+    /// Returns `Err(ChordError::InvalidLength)` if `chroma.len() != SEMITONES`,
+    /// or `Err(ChordError::NonFinite)` if a bin is NaN or infinite.
+    pub fn detect_chord_opt(&mut self, chroma: &[f32]) -> Result<Option<Chord>, ChordError> {
+        check_chroma(chroma)?;
+        let energy: f32 = chroma.iter().map(|c| c * c).sum();
+        if energy < self.silence_threshold {
+            self.chroma_work.copy_from_slice(chroma);
+            self.track_silence();
+            return Ok(None);
+        }
+        self.detect_chord(chroma).map(Some)
+    }
+
+    /// Detect the best chord, or `None` when it is not worth showing.
+    ///
+    /// Returns `None` when the chord's normalized confidence (0.0..=1.0,
+    /// higher is better) is below `min_confidence`, and whenever
+    /// `detect_chord` would report an unknown chord: a silent frame (which
+    /// still counts towards `silence_reset_frames`) or a match rejected by
+    /// `min_match_quality`. Without `min_confidence` only those are
+    /// suppressed. A chord that passes is returned exactly as `detect_chord`
+    /// reports it.
+    ///
+    /// Returns `Err(ChordError::InvalidLength)` if `chroma.len() != SEMITONES`,
+    /// or `Err(ChordError::NonFinite)` if a bin is NaN or infinite.
+    pub fn detect_chord_gated(&mut self, chroma: &[f32]) -> Result<Option<Chord>, ChordError> {
+        let chord = self.detect_chord(chroma)?;
+        let Some(idx) = self.chord_index(chord.root, chord.quality) else {
+            return Ok(None);
+        };
+        if let Some(min) = self.min_confidence {
+            let score = self.scores[idx];
+            let squared = if self.exact_confidence { score * score } else { score };
+            if self.similarity(idx, squared) < min {
+                return Ok(None);
+            }
+        }
+        Ok(Some(chord))
+    }
+
+    /// Detect the single dominant pitch class, for monophonic input such as
+    /// a melody line.
+    ///
+    /// Returns the loudest bin and its strength: its share of the chroma
+    /// sum, in 0.0..=1.0, with negative bins counted as zero. No chord
+    /// templates are involved and the input is not bleed-suppressed. When no
+    /// bin holds at least 40% of the sum, such as on a flat chroma or an
+    /// evenly voiced triad, the note is `NoteName::Unknown` with that strength
+    /// still reported; a silent frame gives `(Unknown, 0.0)`. The call
+    /// counts towards `silence_reset_frames` like the chord detectors.
+    ///
+    /// Returns `Err(ChordError::InvalidLength)` if `chroma.len() != SEMITONES`,
+    /// or `Err(ChordError::NonFinite)` if a bin is NaN or infinite.
+    pub fn detect_pitch(&mut self, chroma: &[f32]) -> Result<(NoteName, f32), ChordError> {
+        check_chroma(chroma)?;
+        self.chroma_work.copy_from_slice(chroma);
+        self.track_silence();
+
+        let energy: f32 = chroma.iter().map(|c| c * c).sum();
+        let total: f32 = chroma.iter().map(|c| c.max(0.0)).sum();
+        if energy < self.silence_threshold || total <= 0.0 {
+            return Ok((NoteName::Unknown, 0.0));
+        }
+        let (idx, &peak) = chroma
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .expect("chroma has 12 bins");
+        let strength = peak / total;
+        if strength < PITCH_DOMINANCE {
+            return Ok((NoteName::Unknown, strength));
+        }
+        Ok((NoteName::from_idx(idx), strength))
     }
 
     /// Detect the top `k` chords from a chromagram slice.
     ///
+    /// A silent frame (see [`ChordDetector::detect_chord`]) returns a single
+    /// unknown chord whatever `k` is.
+    ///
     /// Returns:
     /// - `Err(InvalidLength)` if `chroma.len() != SEMITONES`.
+    /// - `Err(NonFinite)` if a bin is NaN or infinite.
     /// - `Err(InvalidArgument)` if `k == 0`.
     pub fn top_k(&mut self, chroma: &[f32], k: usize) -> Result<Vec<Chord>, ChordError> {
-        if chroma.len() != SEMITONES {
-            return Err(ChordError::InvalidLength {
-                expected: SEMITONES,
-                got: chroma.len(),
-            });
-        }
+        check_chroma(chroma)?;
         if k == 0 {
             return Err(ChordError::InvalidArgument {
                 arg: "k",
                 msg: "must be >= 1".to_string(),
             });
         }
-        let choices = k.min(NUM_CHORDS);
+        let choices = k.min(self.scores.len());
         self.classify_chroma(chroma, choices)
     }
 
+    /// Detect the single best chord after applying a custom transform to the chromagram.
+    ///
+    /// Order of operations:
+    /// 1. `chroma` is copied into the detector's working buffer.
+    /// 2. `transform` is applied to the working copy (the input is never modified).
+    /// 3. Bleed suppression runs on the transformed chroma.
+    /// 4. Every chord profile is scored and the best match is returned.
+    ///
+    /// Returns `Err(ChordError::InvalidLength)` if `chroma.len() != SEMITONES`,
+    /// or `Err(ChordError::NonFinite)` if a bin is NaN or infinite.
+    pub fn detect_with(
+        &mut self,
+        chroma: &[f32],
+        transform: impl Fn(&mut [f32; SEMITONES]),
+    ) -> Result<Chord, ChordError> {
+        check_chroma(chroma)?;
+        self.chroma_work.copy_from_slice(chroma);
+        transform(&mut self.chroma_work);
+        let mut list = self.rank_work(1);
+        Ok(list.remove(0))
+    }
+
+    /// Check whether `target` is the best match for a chromagram slice.
+    ///
+    /// Returns `(is_top, score)`, where `score` is the target's own confidence
+    /// on the same scale as `Chord::confidence` (lower is better unless
+    /// `normalized_confidence` is set). A close-but-not-top match is reported
+    /// as `(false, score)`, so callers can still grade near misses. Ties with
+    /// the best chord count as a match. A silent frame matches no chord and
+    /// reports the worst confidence, like [`ChordDetector::detect_chord`].
+    ///
+    /// Returns:
+    /// - `Err(InvalidLength)` if `chroma.len() != SEMITONES`.
+    /// - `Err(NonFinite)` if a bin is NaN or infinite.
+    /// - `Err(InvalidArgument)` if `target.root` is `NoteName::Unknown`.
+    pub fn is_chord(&mut self, chroma: &[f32], target: &Chord) -> Result<(bool, f32), ChordError> {
+        check_chroma(chroma)?;
+        let idx = self.chord_index(target.root, target.quality).ok_or_else(|| {
+            ChordError::InvalidArgument {
+                arg: "target",
+                msg: "root must be a known note and quality must be scored".to_string(),
+            }
+        })?;
+
+        let best = self.classify_chroma(chroma, 1)?.remove(0);
+        if self.work_is_silent() {
+            return Ok((false, best.confidence));
+        }
+        let best_idx = self.chord_index(best.root, best.quality).unwrap_or(idx);
+        let is_top = self.scores[idx] <= self.scores[best_idx];
+        Ok((is_top, self.confidence_of(idx, self.scores[idx])))
+    }
+
+    /// Convert entry `idx` of `scores` into a reported confidence
+    #[inline]
+    fn confidence_of(&self, idx: usize, score: f32) -> f32 {
+        if self.normalized_confidence {
+            let squared = if self.exact_confidence { score * score } else { score };
+            return self.similarity(idx, squared);
+        }
+        if self.exact_confidence {
+            score
+        } else {
+            sqrtf(score)
+        }
+    }
+
+    /// Map a squared score to 0.0..=1.0 by measuring it against the energy
+    /// of the chroma it was scored on (1.0 = nothing outside the template)
+    fn similarity(&self, idx: usize, squared: f32) -> f32 {
+        let work = match self.seventh_bleed {
+            Some(_) if self.kinds[idx / SEMITONES].is_seventh() => &self.seventh_work,
+            _ => &self.chroma_work,
+        };
+        let energy: f32 = work.iter().map(|c| c * c).sum();
+        if energy <= 0.0 {
+            return 0.0;
+        }
+        (1.0 - squared / (self.profiles[idx].inv_norm_sq * energy)).clamp(0.0, 1.0)
+    }
+
+    /// Score every chord hypothesis and return all of their confidences.
+    ///
+    /// Entry `kind_idx * 12 + root_idx` belongs to the chord with root
+    /// `root_idx` semitones above C and the `kind_idx`-th scored quality
+    /// (`ChordKind` declaration order, or template order with custom
+    /// templates); [`ChordDetector::chord_at`] decodes an index. Values are
+    /// on the same scale as `Chord::confidence`, so with default settings
+    /// the minimum is what `detect_chord` reports. The slice is overwritten
+    /// by the next call.
+    ///
+    /// Returns `Err(ChordError::InvalidLength)` if `chroma.len() != SEMITONES`,
+    /// or `Err(ChordError::NonFinite)` if a bin is NaN or infinite.
+    pub fn score_all(&mut self, chroma: &[f32]) -> Result<&[f32], ChordError> {
+        check_chroma(chroma)?;
+        self.chroma_work.copy_from_slice(chroma);
+        self.score_work();
+
+        for idx in 0..self.scores.len() {
+            self.confidences[idx] = self.confidence_of(idx, self.scores[idx]);
+        }
+        Ok(&self.confidences)
+    }
+
+    /// Root and quality of entry `idx` of [`ChordDetector::score_all`], or
+    /// `None` if `idx` is out of range.
+    pub fn chord_at(&self, idx: usize) -> Option<(NoteName, ChordKind)> {
+        let quality = *self.kinds.get(idx / SEMITONES)?;
+        Some((NoteName::from_idx(idx % SEMITONES), quality))
+    }
+
+    /// Score every chord and lay the results out as a quality × root grid.
+    ///
+    /// The outer `Vec` has one row per scored chord quality, in `ChordKind`
+    /// declaration order (or template order with custom templates); each row
+    /// holds the 12 roots from C to B paired with their
+    /// confidence (on the same scale as `Chord::confidence`). Useful for
+    /// rendering a chord picker colored by match strength.
+    ///
+    /// Returns `Err(ChordError::InvalidLength)` if `chroma.len() != SEMITONES`,
+    /// or `Err(ChordError::NonFinite)` if a bin is NaN or infinite.
+    pub fn ranked_grid(&mut self, chroma: &[f32]) -> Result<Vec<Vec<(NoteName, f32)>>, ChordError> {
+        check_chroma(chroma)?;
+        self.chroma_work.copy_from_slice(chroma);
+        self.score_work();
+
+        Ok((0..self.kinds.len())
+            .map(|kind| {
+                (0..SEMITONES)
+                    .map(|root| {
+                        let idx = kind * SEMITONES + root;
+                        (NoteName::from_idx(root), self.confidence_of(idx, self.scores[idx]))
+                    })
+                    .collect()
+            })
+            .collect())
+    }
+
+    /// Detect the most likely root and how clearly it beats every other root.
+    ///
+    /// Returns the root of the best chord together with the confidence gap
+    /// to the best chord built on any *other* root. Competing qualities on the
+    /// same root (C vs Cmaj7) are ignored, so the gap isolates root certainty
+    /// from quality certainty. Larger gaps mean a more certain root; a gap of
+    /// 0.0 means another root scores equally well. A silent frame returns
+    /// `(NoteName::Unknown, 0.0)`.
+    ///
+    /// Returns `Err(ChordError::InvalidLength)` if `chroma.len() != SEMITONES`,
+    /// or `Err(ChordError::NonFinite)` if a bin is NaN or infinite.
+    pub fn root_confidence(&mut self, chroma: &[f32]) -> Result<(NoteName, f32), ChordError> {
+        check_chroma(chroma)?;
+        self.chroma_work.copy_from_slice(chroma);
+        self.score_work();
+        if self.work_is_silent() {
+            return Ok((NoteName::Unknown, 0.0));
+        }
+
+        // best (index, score) per root
+        let mut per_root = [(0, f32::INFINITY); SEMITONES];
+        for (i, &score) in self.scores.iter().enumerate() {
+            let root = i % SEMITONES;
+            if score < per_root[root].1 {
+                per_root[root] = (i, score);
+            }
+        }
+
+        let mut order: [usize; SEMITONES] = core::array::from_fn(|i| i);
+        order.sort_by(|&a, &b| per_root[a].1.total_cmp(&per_root[b].1));
+        let (best, runner_up) = (per_root[order[0]], per_root[order[1]]);
+
+        let gap = (self.confidence_of(runner_up.0, runner_up.1)
+            - self.confidence_of(best.0, best.1))
+        .abs();
+        Ok((NoteName::from_idx(order[0]), gap))
+    }
+
     /// Core pipeline returning exactly `choices` chords.
     fn classify_chroma(
         &mut self,
         chroma: &[f32],
         choices: usize,
     ) -> Result<Vec<Chord>, ChordError> {
-        // 1) bleed suppression
         self.chroma_work.copy_from_slice(chroma);
-        for i in 0..SEMITONES {
-            let bleed_amt = self.bleed * self.chroma_work[i];
-            let target = (i + SEMITONES - 5) % SEMITONES; // shift down a perfect fourth
-            let reduced = (self.chroma_work[target] - bleed_amt).max(0.0);
-            self.chroma_work[target] = reduced;
-        }
+        Ok(self.rank_work(choices))
+    }
 
-        // 2) score each profile
-        for (i, p) in self.profiles.iter().enumerate() {
-            self.scores[i] = score_chord(&self.chroma_work, p);
+    /// Suppress bleed in `chroma_work`, score it and return the best `choices` chords.
+    ///
+    /// A silent input returns a single unknown chord instead, see
+    /// [`ChordDetector::silent_chord`].
+    fn rank_work(&mut self, choices: usize) -> Vec<Chord> {
+        self.score_work();
+        if self.work_is_silent() {
+            return vec![self.silent_chord()];
         }
 
         // 3) pick top k
@@ -318,12 +2022,15 @@ impl ChordDetector {
             self.idx_scores.push((i, s));
         }
 
-        // place the `choices` smallest scores in front
-        self.idx_scores
-            .select_nth_unstable_by(choices, |a, b| a.1.partial_cmp(&b.1).unwrap());
+        // place the `choices` smallest scores in front; exact ties go to the
+        // earlier quality, so a bare triad is not reported as a seventh
+        let by_score =
+            |a: &(usize, f32), b: &(usize, f32)| cmp_scores(a.1, b.1).then(a.0.cmp(&b.0));
+        if choices < self.idx_scores.len() {
+            self.idx_scores.select_nth_unstable_by(choices, by_score);
+        }
         // sort those front elements
-        self.idx_scores[..choices]
-            .sort_unstable_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+        self.idx_scores[..choices].sort_unstable_by(by_score);
 
         let mut result = Vec::with_capacity(choices);
         for &(idx, score) in &self.idx_scores[..choices] {
@@ -331,15 +2038,320 @@ impl ChordDetector {
             let root_idx = idx % SEMITONES;
             result.push(Chord {
                 root: NoteName::from_idx(root_idx),
-                quality: CHORD_KINDS[kind_idx],
-                confidence: score,
+                quality: self.kinds[kind_idx],
+                confidence: self.confidence_of(idx, score),
+                bass: self.detect_bass.then(|| self.loudest_tone(idx)),
             });
         }
-        Ok(result)
+        result
+    }
+
+    /// Whether the input last passed to `score_work` is below
+    /// `silence_threshold`
+    fn work_is_silent(&self) -> bool {
+        let energy: f32 = self.input_work.iter().map(|c| c * c).sum();
+        energy < self.silence_threshold
+    }
+
+    /// The chord reported for a silent frame.
+    ///
+    /// Every profile scores a perfect 0.0 residual on an all-zero chroma, so
+    /// ranking would return an arbitrary chord that looks like an exact
+    /// match. Instead the root and quality are unknown and the confidence is
+    /// the worst possible: 0.0 with `normalized_confidence`, otherwise
+    /// infinity.
+    fn silent_chord(&self) -> Chord {
+        Chord {
+            root: NoteName::Unknown,
+            quality: ChordKind::Unknown,
+            confidence: if self.normalized_confidence { 0.0 } else { f32::INFINITY },
+            bass: None,
+        }
+    }
+
+    /// Chord tone of profile `idx` with the most energy in the input chroma
+    fn loudest_tone(&self, idx: usize) -> NoteName {
+        let root = idx % SEMITONES;
+        let weights = &self.profiles[idx].weights;
+        let mut best = root;
+        // Walk up from the root so ties do not depend on transposition
+        for i in (root..root + SEMITONES).map(|i| i % SEMITONES) {
+            if weights[i] >= 1.0 && self.input_work[i] > self.input_work[best] {
+                best = i;
+            }
+        }
+        NoteName::from_idx(best)
+    }
+
+    /// Index into the profile table for a root and quality
+    #[inline]
+    fn chord_index(&self, root: NoteName, quality: ChordKind) -> Option<usize> {
+        let root_idx = root.to_semitone()?;
+        let kind_idx = self.kinds.iter().position(|&k| k == quality)?;
+        Some(kind_idx * SEMITONES + root_idx)
+    }
+
+    /// Fraction of the last scored chroma's energy on the tones of `chord`
+    fn match_quality(&self, chord: &Chord) -> f32 {
+        let Some(idx) = self.chord_index(chord.root, chord.quality) else {
+            return 0.0;
+        };
+        let work = match self.seventh_bleed {
+            Some(_) if chord.quality.is_seventh() => &self.seventh_work,
+            _ => &self.chroma_work,
+        };
+        let p = &self.profiles[idx];
+        let total: f32 = work.iter().map(|c| c * c).sum();
+        if total <= 0.0 {
+            return 0.0;
+        }
+        let on_chord: f32 = (0..SEMITONES).map(|i| p.weights[i] * work[i] * work[i]).sum();
+        on_chord / total
+    }
+
+    /// Clear streaming history so the next frame is analyzed as if it were
+    /// the first. Configuration is kept.
+    pub fn reset(&mut self) {
+        self.clear_history();
+        self.silent_frames = 0;
+    }
+
+    /// Drop state carried between frames
+    fn clear_history(&mut self) {
+        self.history.clear();
+        self.smoothed_scores.clear();
+    }
+
+    /// Count consecutive silent frames and clear history after a long pause
+    fn track_silence(&mut self) {
+        let energy: f32 = self.chroma_work.iter().map(|c| c * c).sum();
+        if energy >= self.silence_threshold {
+            self.silent_frames = 0;
+            return;
+        }
+        self.silent_frames += 1;
+        if self.silent_frames == self.silence_reset_frames {
+            self.clear_history();
+        }
+    }
+
+    /// Suppress bleed in `chroma_work` and score every profile into `scores`.
+    fn score_work(&mut self) {
+        self.track_silence();
+        self.input_work = self.chroma_work;
+
+        // 1) bleed suppression
+        if let Some(seventh_bleed) = self.seventh_bleed {
+            self.seventh_work = self.chroma_work;
+            suppress_bleed(&mut self.seventh_work, seventh_bleed, self.bleed_offset);
+        }
+        suppress_bleed(&mut self.chroma_work, self.bleed, self.bleed_offset);
+
+        // 2) score each profile
+        // allow_rootless only matters with a completeness term, so it
+        // implies demanding complete voicings
+        let tolerance = self.missing_note_tolerance.or(self.allow_rootless.then_some(0));
+        for (i, p) in self.profiles.iter().enumerate() {
+            let work = match self.seventh_bleed {
+                Some(_) if self.kinds[i / SEMITONES].is_seventh() => &self.seventh_work,
+                _ => &self.chroma_work,
+            };
+            let mut score = score_chord(work, p);
+            if let Some(tolerance) = tolerance {
+                let rootless = self.allow_rootless && self.kinds[i / SEMITONES].is_seventh();
+                let optional = rootless.then_some(i % SEMITONES);
+                score += missing_penalty(work, p, tolerance, optional);
+            }
+            if self.weight_bass > 0.0 {
+                score += self.weight_bass * weak_root_penalty(work, p, i % SEMITONES);
+            }
+            self.scores[i] = if self.exact_confidence { sqrtf(score) } else { score };
+        }
+    }
+}
+
+/// Append one profile per root for a chord shape
+fn push_profiles(
+    profiles: &mut Vec<PrecalcProfile>,
+    intervals: &[usize],
+    interval_count: f32,
+    bias: f32,
+    model: TemplateModel,
+) {
+    for root in 0..SEMITONES {
+        let mut weights = [0.0; SEMITONES];
+        if model == TemplateModel::Harmonic {
+            for &off in intervals {
+                let mut weight = 1.0;
+                for &shift in &PARTIAL_PITCH_CLASSES[1..] {
+                    weight *= TEMPLATE_HARMONIC_DECAY;
+                    let pc = (root + off + shift) % SEMITONES;
+                    weights[pc] = f32::max(weights[pc], weight);
+                }
+            }
+        }
+        for &off in intervals {
+            weights[(root + off) % SEMITONES] = 1.0;
+        }
+
+        // Off-chord weight left to penalise; equals 12 - tones when binary.
+        // A template covering all 12 tones still gets a finite normalizer
+        let off_chord = match model {
+            TemplateModel::Binary => (SEMITONES as f32) - interval_count,
+            TemplateModel::Harmonic => weights.iter().map(|w| 1.0 - w).sum(),
+        };
+        let inv = 1.0 / (off_chord.max(1.0) * bias);
+        profiles.push(PrecalcProfile {
+            weights,
+            inv_norm_sq: inv * inv,
+        });
+    }
+}
+
+/// Ensure a chromagram slice has exactly `SEMITONES` finite bins
+#[inline]
+fn check_chroma(chroma: &[f32]) -> Result<(), ChordError> {
+    if chroma.len() != SEMITONES {
+        return Err(ChordError::InvalidLength {
+            expected: SEMITONES,
+            got: chroma.len(),
+        });
+    }
+    if let Some(index) = chroma.iter().position(|c| !c.is_finite()) {
+        return Err(ChordError::NonFinite { index });
+    }
+    Ok(())
+}
+
+/// Order scores ascending, with NaN after every number
+#[inline]
+fn cmp_scores(a: f32, b: f32) -> Ordering {
+    match (a.is_nan(), b.is_nan()) {
+        (false, false) => a.total_cmp(&b),
+        (a_nan, b_nan) => a_nan.cmp(&b_nan),
+    }
+}
+
+/// Reject a factor outside 0.0..=1.0 (NaN included)
+fn check_unit_range(arg: &'static str, value: f32) -> Result<(), ChordError> {
+    if (0.0..=1.0).contains(&value) {
+        Ok(())
+    } else {
+        Err(ChordError::InvalidArgument {
+            arg,
+            msg: format!("must be in 0.0..=1.0, got {value}"),
+        })
+    }
+}
+
+/// Reject custom templates that cannot be scored or indexed
+fn check_templates(templates: &[ChordTemplate]) -> Result<(), ChordError> {
+    if templates.len() > usize::from(u16::MAX) + 1 {
+        return Err(ChordError::InvalidArgument {
+            arg: "templates",
+            msg: format!("at most 65536 templates are supported, got {}", templates.len()),
+        });
+    }
+    for (i, t) in templates.iter().enumerate() {
+        if !(t.bias.is_finite() && t.bias > 0.0) {
+            return Err(ChordError::InvalidArgument {
+                arg: "templates",
+                msg: format!("template {i} bias must be positive and finite, got {}", t.bias),
+            });
+        }
+        let mask = t.intervals.iter().fold(0u16, |m, &off| m | 1 << (off % SEMITONES));
+        let tones = mask.count_ones();
+        if tones == 0 || tones as usize == SEMITONES {
+            return Err(ChordError::InvalidArgument {
+                arg: "templates",
+                msg: format!("template {i} must have 1 to 11 pitch classes, got {tones}"),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Detect the best chord without a `ChordDetector`.
+///
+/// Applies bleed suppression with `bleed` and scores all built-in chord
+/// qualities against a local copy of `chroma`, building each template on the
+/// fly. No state is kept between calls, so this can run inside pure closures,
+/// but every call re-derives all profiles: expect it to be several times
+/// slower than [`ChordDetector::detect_chord`], which precomputes them once.
+/// For the same `bleed` it agrees with a default-configured detector,
+/// including on silent input (energy below 1e-4), which is reported as an
+/// unknown chord with infinite confidence.
+///
+/// Returns `Err(ChordError::InvalidArgument)` if `bleed` is outside
+/// 0.0..=1.0 or not finite, like [`ChordDetectorBuilder::try_build`].
+pub fn detect_chord_stateless(
+    chroma: &[f32; SEMITONES],
+    bleed: f32,
+) -> Result<Chord, ChordError> {
+    check_unit_range("bleed", bleed)?;
+    let energy: f32 = chroma.iter().map(|c| c * c).sum();
+    if energy < DEFAULT_SILENCE_THRESHOLD {
+        return Ok(Chord {
+            root: NoteName::Unknown,
+            quality: ChordKind::Unknown,
+            confidence: f32::INFINITY,
+            bass: None,
+        });
+    }
+    let mut work = *chroma;
+    suppress_bleed(&mut work, bleed, bleed_offset(DEFAULT_BLEED_INTERVAL));
+
+    let mut best = (0, f32::INFINITY);
+    for (kind_idx, &(_kind, bias, interval_count)) in CHORD_SPECS.iter().enumerate() {
+        let inv = 1.0 / (((SEMITONES as f32) - interval_count) * bias);
+        for root in 0..SEMITONES {
+            let mut tones = [false; SEMITONES];
+            for &off in CHORD_INTERVALS[kind_idx] {
+                tones[(root + off) % SEMITONES] = true;
+            }
+
+            let mut acc = 0.0;
+            for (i, &c) in work.iter().enumerate() {
+                if !tones[i] {
+                    acc += c * c;
+                }
+            }
+            let score = acc * (inv * inv);
+            if score < best.1 {
+                best = (kind_idx * SEMITONES + root, score);
+            }
+        }
+    }
+
+    Ok(Chord {
+        root: NoteName::from_idx(best.0 % SEMITONES),
+        quality: CHORD_KINDS[best.0 / SEMITONES],
+        confidence: sqrtf(best.1),
+        bass: None,
+    })
+}
+
+/// Upward pitch-class offset equivalent to a signed `bleed_interval`
+fn bleed_offset(interval: i8) -> usize {
+    interval.rem_euclid(SEMITONES as i8) as usize
+}
+
+/// Subtract `bleed` times each bin from the bin `offset` semitones above it
+/// (mod 12; 7 is a perfect fourth below)
+#[inline]
+fn suppress_bleed(chroma: &mut Chromagram, bleed: f32, offset: usize) {
+    for i in 0..SEMITONES {
+        let bleed_amt = bleed * chroma[i];
+        let target = (i + offset) % SEMITONES;
+        let reduced = (chroma[target] - bleed_amt).max(0.0);
+        chroma[target] = reduced;
     }
 }
 
-/// Score a single chord profile against the chromagram
+/// Score a single chord profile against the chromagram.
+///
+/// Returns the squared score; callers take the square root when they need the
+/// reported confidence.
 #[inline(always)]
 fn score_chord(chroma: &Chromagram, p: &PrecalcProfile) -> f32 {
     let mut acc = 0.0;
@@ -348,7 +2360,41 @@ fn score_chord(chroma: &Chromagram, p: &PrecalcProfile) -> f32 {
         let c = chroma[i];
         acc += miss * (c * c);
     });
-    acc.sqrt() * p.inv_norm
+    acc * p.inv_norm_sq
+}
+
+/// Squared-score penalty for chord tones missing beyond `tolerance`; the tone
+/// at `optional` never counts as missing.
+#[inline]
+fn missing_penalty(
+    chroma: &Chromagram,
+    p: &PrecalcProfile,
+    tolerance: usize,
+    optional: Option<usize>,
+) -> f32 {
+    let peak = chroma.iter().cloned().fold(0.0_f32, f32::max);
+    let missing = (0..SEMITONES)
+        .filter(|&i| Some(i) != optional)
+        .filter(|&i| p.weights[i] >= 1.0 && chroma[i] < MISSING_TONE_RATIO * peak)
+        .count();
+    missing.saturating_sub(tolerance) as f32 * (peak * peak) * p.inv_norm_sq
+}
+
+/// Squared-score penalty for a root quieter than the loudest chord tone:
+/// `(1 - root / loudest) * loudest^2`, normalized like `score_chord`.
+#[inline]
+fn weak_root_penalty(chroma: &Chromagram, p: &PrecalcProfile, root: usize) -> f32 {
+    if p.weights[root] < 1.0 {
+        return 0.0;
+    }
+    let loudest = (0..SEMITONES)
+        .filter(|&i| p.weights[i] >= 1.0)
+        .map(|i| chroma[i])
+        .fold(0.0_f32, f32::max);
+    if loudest <= 0.0 {
+        return 0.0;
+    }
+    (1.0 - chroma[root] / loudest) * (loudest * loudest) * p.inv_norm_sq
 }
 
 impl Default for ChordDetector {
@@ -9,15 +9,36 @@ use std::{
     f32::consts::PI,
     sync::Arc,
 };
-use rustfft::{num_complex::Complex, Fft, FftPlanner};
+use realfft::{num_complex::Complex, FftNum, RealFftPlanner, RealToComplex};
 use thiserror::Error;
 
+use crate::chord_detector::NoteName;
+#[cfg(feature = "sliding-dft")]
+use crate::sliding_dft::SlidingDft;
+
 const SEMITONES: usize = 12;
-const BUFFER_SIZE: usize = 8192;
-const CHROMA_INTERVAL: usize = BUFFER_SIZE / 2;
+const DEFAULT_BUFFER_SIZE: usize = 8192;
+
+/// Frequency of C3 in 440 Hz equal temperament, the pitch-class reference
+const C3_HZ: f32 = 130.8127;
+const DEFAULT_TUNING_HZ: f32 = 440.0;
+const TUNING_RANGE_HZ: (f32, f32) = (400.0, 480.0);
+
+/// Most spectra the harmonic-emphasis median filter keeps, the `2k + 1`
+/// of the default hop (`k = 8`)
+const MAX_MEDIAN_SPECTRA: usize = 17;
+
+/// Fraction of the way the tuning estimate moves towards each new measurement
+const TUNING_ADAPTATION: f32 = 0.1;
+
+/// Default normalized spectral flux that counts as an onset
+const DEFAULT_ONSET_THRESHOLD: f32 = 0.3;
+
+/// Weight of the peak-normalized bass stem in `fuse_stem_chroma`
+const BASS_STEM_WEIGHT: f32 = 0.5;
 
-#[allow(clippy::large_const_arrays)]
-const HAMMING_WINDOW: [f32; BUFFER_SIZE] = make_hamming_window();
+/// Lowest and highest frequencies folded by `mel_to_chroma` (A0..C8)
+const MUSICAL_RANGE_HZ: (f32, f32) = (27.5, 4186.01);
 
 /// Errors returned by the Chromagram pipeline.
 #[derive(Debug, Error)]
@@ -31,19 +52,313 @@ pub enum ChromagramError {
         got: usize,
     },
 
+    /// Magnitude spectrum received was not `buffer_size / 2 + 1` bins long.
+    #[error("expected magnitude spectrum of {expected} bins, got {got}")]
+    InvalidSpectrumSize {
+        /// The expected number of bins.
+        expected: usize,
+        /// The actual number of bins received.
+        got: usize,
+    },
+
+    /// Interleaved input was given with zero channels.
+    #[error("channel count must be at least 1")]
+    InvalidChannels,
+
     /// An error occurred during the configuration of the Chromagram.
     #[error("configuration error: {0}")]
     Configuration(String),
 }
 
+/// Audio sample type accepted by the chromagram: `f32` or `f64`.
+///
+/// The buffer, window and FFT run at the sample type's precision; the
+/// magnitude spectrum and the chroma are `f32` either way, so the chord
+/// detector is unaffected. This trait is sealed.
+pub trait Sample: FftNum + private::Sealed {
+    /// Square root
+    fn sqrt(self) -> Self;
+    /// Convert to `f32`, rounding if needed
+    fn to_f32(self) -> f32;
+    /// Convert to `f64` without loss
+    fn to_f64(self) -> f64;
+}
+
+impl Sample for f32 {
+    fn sqrt(self) -> Self {
+        f32::sqrt(self)
+    }
+    fn to_f32(self) -> f32 {
+        self
+    }
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+}
+
+impl Sample for f64 {
+    fn sqrt(self) -> Self {
+        f64::sqrt(self)
+    }
+    fn to_f32(self) -> f32 {
+        self as f32
+    }
+    fn to_f64(self) -> f64 {
+        self
+    }
+}
+
+mod private {
+    pub trait Sealed {}
+    impl Sealed for f32 {}
+    impl Sealed for f64 {}
+}
+
+/// Window function applied to the analysis buffer before the FFT.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum WindowKind {
+    /// Hamming window (default).
+    #[default]
+    Hamming,
+    /// Hann window.
+    Hann,
+    /// Classic three-term Blackman window.
+    Blackman,
+    /// Four-term Blackman-Harris window, the strongest sidelobe suppression.
+    BlackmanHarris,
+}
+
+impl WindowKind {
+    /// Symmetric window coefficients of length `len`.
+    pub fn coefficients(self, len: usize) -> Vec<f32> {
+        let cosine_terms = self.cosine_terms();
+        let denom = (len.max(2) - 1) as f32;
+        (0..len)
+            .map(|n| {
+                let phase = 2.0 * PI * n as f32 / denom;
+                // a0 - a1 cos(x) + a2 cos(2x) - a3 cos(3x)
+                cosine_terms
+                    .iter()
+                    .enumerate()
+                    .map(|(k, a)| {
+                        let sign = if k % 2 == 0 { 1.0 } else { -1.0 };
+                        sign * a * (k as f32 * phase).cos()
+                    })
+                    .sum()
+            })
+            .collect()
+    }
+
+    /// Coefficients `a0, a1, ...` of the window's cosine series
+    const fn cosine_terms(self) -> &'static [f32] {
+        match self {
+            WindowKind::Hamming => &[0.54, 0.46],
+            WindowKind::Hann => &[0.5, 0.5],
+            WindowKind::Blackman => &[0.42, 0.5, 0.08],
+            WindowKind::BlackmanHarris => &[0.35875, 0.48829, 0.14128, 0.01168],
+        }
+    }
+}
+
+/// Spectral analysis used to fold the buffer into pitch classes.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum TransformKind {
+    /// Pick the strongest FFT bin near every note and its harmonics (default).
+    #[default]
+    Fft,
+    /// Sum the FFT magnitudes through a log-spaced filterbank with one
+    /// constant-Q band per semitone.
+    ConstantQ,
+}
+
+/// Anti-alias filter run ahead of decimation.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum DecimationQuality {
+    /// Second-order Butterworth low-pass, a single biquad (default).
+    #[default]
+    Fast,
+    /// Eighth-order Butterworth low-pass, four cascaded biquads: about
+    /// four times the CPU per input sample, with a much steeper rolloff
+    /// above the decimated Nyquist frequency.
+    High,
+}
+
+impl DecimationQuality {
+    /// Number of biquad sections in the cascade
+    const fn stages(self) -> usize {
+        match self {
+            DecimationQuality::Fast => 1,
+            DecimationQuality::High => MAX_FILTER_STAGES,
+        }
+    }
+}
+
+/// Normalization applied to every emitted chromagram.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum NormKind {
+    /// Leave the raw bin energies (default).
+    #[default]
+    None,
+    /// Scale so the bins sum to 1.
+    L1,
+    /// Scale to unit Euclidean length.
+    L2,
+    /// Scale so the loudest bin is 1.
+    Max,
+}
+
+/// One semitone-wide band of the constant-Q filterbank
+#[derive(Clone)]
+struct CqBand {
+    /// First FFT bin covered by the band
+    start: usize,
+    /// Weight of bins `start..start + weights.len()`
+    weights: Vec<f32>,
+}
+
+/// Biquad sections in the `DecimationQuality::High` cascade
+const MAX_FILTER_STAGES: usize = 4;
+
+/// Second-order low-pass section run ahead of decimation, with its state
+/// carried from frame to frame
+#[derive(Clone, Copy)]
+struct Biquad<T> {
+    b0: T,
+    b1: T,
+    b2: T,
+    a1: T,
+    a2: T,
+    x1: T,
+    x2: T,
+    y1: T,
+    y2: T,
+}
+
+impl<T: Sample> Biquad<T> {
+    /// Butterworth cascade of `stages` sections with its cutoff at the
+    /// Nyquist frequency of the decimated signal, designed with the bilinear
+    /// transform. Only the first `stages` sections are meant to run.
+    fn anti_alias(downsample_factor: usize, stages: usize) -> [Self; MAX_FILTER_STAGES] {
+        let k = (std::f64::consts::PI / (2.0 * downsample_factor as f64)).tan();
+        let order = 2 * stages;
+        core::array::from_fn(|i| {
+            // Pole pair i of an order-`order` Butterworth prototype
+            let pair = i.min(stages - 1);
+            let angle = std::f64::consts::PI * (2 * pair + 1) as f64 / (2 * order) as f64;
+            Self::low_pass(k, 1.0 / (2.0 * angle.cos()))
+        })
+    }
+
+    /// Second-order low-pass with prewarped cutoff `k` and quality factor `q`
+    fn low_pass(k: f64, q: f64) -> Self {
+        let lift64 = |v: f64| T::from_f64(v).expect("f32 and f64 represent every f64 coefficient");
+        let norm = 1.0 / (1.0 + k / q + k * k);
+        Biquad {
+            b0: lift64(k * k * norm),
+            b1: lift64(2.0 * k * k * norm),
+            b2: lift64(k * k * norm),
+            a1: lift64(2.0 * (k * k - 1.0) * norm),
+            a2: lift64((1.0 - k / q + k * k) * norm),
+            x1: T::zero(),
+            x2: T::zero(),
+            y1: T::zero(),
+            y2: T::zero(),
+        }
+    }
+
+    /// Filter one sample (direct form I)
+    #[inline]
+    fn process(&mut self, x0: T) -> T {
+        let y0 = self.b0 * x0 + self.b1 * self.x1 + self.b2 * self.x2
+            - self.a1 * self.y1
+            - self.a2 * self.y2;
+        (self.x2, self.x1) = (self.x1, x0);
+        (self.y2, self.y1) = (self.y1, y0);
+        y0
+    }
+
+    /// Forget the filter history
+    fn reset(&mut self) {
+        (self.x1, self.x2, self.y1, self.y2) = (T::zero(), T::zero(), T::zero(), T::zero());
+    }
+}
+
+/// Linear-interpolation resampler from the input rate to `sampling_rate`,
+/// with its position carried from frame to frame
+#[derive(Clone, Copy)]
+struct Resampler<T> {
+    /// Input samples per output sample
+    step: f64,
+    /// Position of the next output sample, in input samples from the start
+    /// of the next frame (-1.0 is the last sample of the previous frame)
+    pos: f64,
+    /// Last sample of the previous frame
+    last: T,
+}
+
+impl<T: Sample> Resampler<T> {
+    fn new(input_rate: usize, output_rate: usize) -> Self {
+        Resampler {
+            step: input_rate as f64 / output_rate as f64,
+            pos: 0.0,
+            last: T::zero(),
+        }
+    }
+
+    /// Append the output samples that fall within `frame` to `out`
+    fn process(&mut self, frame: &[T], out: &mut Vec<T>) {
+        let Some(&newest) = frame.last() else {
+            return;
+        };
+        let end = (frame.len() - 1) as f64;
+        while self.pos < end {
+            let i = self.pos.floor();
+            let frac = T::from_f64(self.pos - i).expect("f32 and f64 represent a fraction");
+            let a = if i < 0.0 { self.last } else { frame[i as usize] };
+            let b = frame[(i + 1.0) as usize];
+            out.push(a + (b - a) * frac);
+            self.pos += self.step;
+        }
+        self.pos -= frame.len() as f64;
+        self.last = newest;
+    }
+
+    /// Forget the position and the previous frame
+    fn reset(&mut self) {
+        self.pos = 0.0;
+        self.last = T::zero();
+    }
+}
+
 /// Builder for a Chromagram pipeline.
 pub struct ChromagramBuilder {
     frame_size: usize,
     sampling_rate: usize,
+    input_rate: Option<usize>,
     downsample_factor: usize,
     num_harmonics: usize,
     num_octaves: usize,
+    octave_weights: Option<Vec<f32>>,
     search_width: usize,
+    buffer_size: usize,
+    note_carryover: f32,
+    resolve_harmonic_collisions: bool,
+    peak_interpolation: bool,
+    harmonic_emphasis: bool,
+    unchecked: bool,
+    smoothing_alpha: f32,
+    harmonic_rolloff: f32,
+    tuning_hz: f32,
+    equal_loudness: bool,
+    window: WindowKind,
+    transform: TransformKind,
+    hop_size: Option<usize>,
+    normalize: NormKind,
+    onset_threshold: f32,
+    antialias: bool,
+    decimation_quality: DecimationQuality,
+    #[cfg(feature = "sliding-dft")]
+    incremental_spectrum: bool,
 }
 
 impl ChromagramBuilder {
@@ -55,10 +370,31 @@ impl ChromagramBuilder {
         ChromagramBuilder {
             frame_size: 1024,
             sampling_rate: 44_100,
+            input_rate: None,
             downsample_factor: 4,
             num_harmonics: 2,
             num_octaves: 2,
+            octave_weights: None,
             search_width: 3,
+            buffer_size: DEFAULT_BUFFER_SIZE,
+            note_carryover: 0.0,
+            resolve_harmonic_collisions: false,
+            peak_interpolation: false,
+            harmonic_emphasis: false,
+            unchecked: false,
+            smoothing_alpha: 1.0,
+            harmonic_rolloff: 1.0,
+            tuning_hz: DEFAULT_TUNING_HZ,
+            equal_loudness: false,
+            window: WindowKind::Hamming,
+            transform: TransformKind::Fft,
+            hop_size: None,
+            normalize: NormKind::None,
+            onset_threshold: DEFAULT_ONSET_THRESHOLD,
+            antialias: true,
+            decimation_quality: DecimationQuality::Fast,
+            #[cfg(feature = "sliding-dft")]
+            incremental_spectrum: false,
         }
     }
 
@@ -74,12 +410,61 @@ impl ChromagramBuilder {
         self
     }
 
+    /// Accept audio at `rate` Hz and resample it to `sampling_rate` before
+    /// analysis (default: the input is already at `sampling_rate`).
+    ///
+    /// Lets a device locked to e.g. 48 kHz feed a pipeline configured for
+    /// 44.1 kHz without shifting every note. Frames passed to `next` still
+    /// hold `frame_size` samples, now at the input rate. Resampling is by
+    /// linear interpolation: pitch is preserved exactly, but the
+    /// interpolation itself attenuates high frequencies slightly and adds
+    /// faint images, so feed audio at `sampling_rate` when you can. Hop,
+    /// latency and position counts (`samples_until_next`,
+    /// `latency_samples`, `last_frame_sample`) are then in samples at
+    /// `sampling_rate`. A rate equal to `sampling_rate` disables the
+    /// resampler.
+    pub fn input_rate(mut self, rate: usize) -> Self {
+        self.input_rate = Some(rate);
+        self
+    }
+
     /// Set the downsample factor for processing.
+    ///
+    /// `1` analyzes the input at its own rate, skipping both decimation and
+    /// the anti-alias filter (see `antialias`).
     pub fn downsample_factor(mut self, factor: usize) -> Self {
         self.downsample_factor = factor;
         self
     }
 
+    /// Low-pass the input with the anti-alias filter chosen by
+    /// `decimation_quality` before decimating (default true).
+    ///
+    /// Disable it for input that is already band-limited below
+    /// `sampling_rate / (2 * downsample_factor)`, e.g. when a better filter
+    /// runs upstream, to avoid filtering twice. Without the filter, content
+    /// above that frequency aliases into the spectrum, whose bins are
+    /// `sampling_rate / downsample_factor / buffer_size` Hz wide. With
+    /// `downsample_factor(1)` nothing is decimated and the filter never
+    /// runs, so samples reach the analysis buffer unchanged either way.
+    pub fn antialias(mut self, enabled: bool) -> Self {
+        self.antialias = enabled;
+        self
+    }
+
+    /// Choose the anti-alias filter run before decimating (default `Fast`).
+    ///
+    /// The `Fast` biquad rolls off gently, so at large `downsample_factor`
+    /// values (8, 16) content just above the decimated Nyquist frequency
+    /// still aliases noticeably into the spectrum. `High` cascades four
+    /// sections for an eighth-order response at about four times the
+    /// filtering cost. Both keep their state across frames and never
+    /// allocate per frame. Has no effect when `antialias` is off.
+    pub fn decimation_quality(mut self, quality: DecimationQuality) -> Self {
+        self.decimation_quality = quality;
+        self
+    }
+
     /// Set the number of harmonics to consider for chord detection.
     pub fn num_harmonics(mut self, n: usize) -> Self {
         self.num_harmonics = n;
@@ -92,41 +477,422 @@ impl ChromagramBuilder {
         self
     }
 
+    /// Scale each octave's contribution to the chroma, lowest octave first
+    /// (default all 1.0).
+    ///
+    /// Must hold exactly `num_octaves` non-negative weights. Weighting the
+    /// lowest octave up favours the register where fundamentals and the
+    /// bass sit over the overtones above, which steadies the chord root.
+    /// The weights also apply to [`Chromagram::octave_energies`].
+    pub fn octave_weights(mut self, weights: Vec<f32>) -> Self {
+        self.octave_weights = Some(weights);
+        self
+    }
+
     /// Set the search width for finding spectral peaks.
     pub fn search_width(mut self, w: usize) -> Self {
         self.search_width = w;
         self
     }
 
-    /// Finalize and create the Chromagram.
-    pub fn build(self) -> Result<Chromagram, ChromagramError> {
-        if BUFFER_SIZE % self.downsample_factor != 0 {
-            return Err(ChromagramError::Configuration(
-                "BUFFER_SIZE must be divisible by downsample_factor".into(),
+    /// Set the FFT length in downsampled samples (power of two, default 8192).
+    ///
+    /// Larger buffers give finer frequency resolution at the cost of latency
+    /// and CPU; the hop between chromagrams scales with the buffer. Must be
+    /// divisible by `downsample_factor`.
+    pub fn buffer_size(mut self, size: usize) -> Self {
+        self.buffer_size = size;
+        self
+    }
+
+    /// Set how quickly harmonic contributions decay: harmonic `h` is weighted
+    /// `1 / h^rolloff` (default 1.0).
+    ///
+    /// `0.0` weights all harmonics equally, `2.0` favours the fundamental
+    /// more strongly. Match it to the harmonic decay of the instrument.
+    pub fn harmonic_rolloff(mut self, rolloff: f32) -> Self {
+        self.harmonic_rolloff = rolloff;
+        self
+    }
+
+    /// Set the reference pitch of A4 in Hz (400.0..=480.0, default 440.0).
+    ///
+    /// All pitch-class bins are placed relative to this reference, so music
+    /// tuned to e.g. A4 = 432 Hz lands in the right bins.
+    pub fn tuning_hz(mut self, a4: f32) -> Self {
+        self.tuning_hz = a4;
+        self
+    }
+
+    /// Weight the magnitude spectrum by the A-weighting curve before folding
+    /// it into pitch classes (default false).
+    ///
+    /// Uses the IEC 61672 A-weighting magnitude response, normalized to 1.0
+    /// at 1 kHz, so the chroma reflects perceived rather than physical
+    /// energy: low notes are attenuated relative to mid and high ones.
+    pub fn equal_loudness(mut self, enabled: bool) -> Self {
+        self.equal_loudness = enabled;
+        self
+    }
+
+    /// Set the window applied before the FFT (default Hamming).
+    ///
+    /// `BlackmanHarris` suppresses sidelobes far better, which gives cleaner
+    /// peak picking when partials are closely spaced, at the cost of a wider
+    /// main lobe.
+    pub fn window(mut self, kind: WindowKind) -> Self {
+        self.window = kind;
+        self
+    }
+
+    /// Set how many input samples accumulate between chromagrams
+    /// (1..=buffer_size, default buffer_size / 2).
+    ///
+    /// A smaller hop emits more often with more overlap between successive
+    /// analysis windows, for low-latency display; a larger one is cheaper
+    /// for batch scanning. Counted in input samples, before downsampling.
+    pub fn hop_size(mut self, hop: usize) -> Self {
+        self.hop_size = Some(hop);
+        self
+    }
+
+    /// Set how each chromagram is normalized (default `NormKind::None`).
+    ///
+    /// Raw bins scale with input loudness; normalizing makes thresholds and
+    /// comparisons across recordings independent of level. Applied before
+    /// `note_carryover` and `smoothing_alpha`. An all-zero chromagram is
+    /// left at zero.
+    pub fn normalize(mut self, kind: NormKind) -> Self {
+        self.normalize = kind;
+        self
+    }
+
+    /// Set the normalized spectral flux at which an emission counts as an
+    /// onset (0.0 < threshold <= 1.0, default 0.3).
+    ///
+    /// See [`Chromagram::spectral_flux`]. Lower values also flag softer
+    /// changes, such as a single added note, as onsets.
+    pub fn onset_threshold(mut self, threshold: f32) -> Self {
+        self.onset_threshold = threshold;
+        self
+    }
+
+    /// Set how the spectrum is folded into pitch classes (default `Fft`).
+    ///
+    /// `ConstantQ` covers `num_octaves` octaves upwards from C3 with one
+    /// band per semitone, Hann-shaped in log frequency and reaching a
+    /// quarter-tone either side of its note. Every band is as wide in cents
+    /// as the next, so low notes are not smeared into their neighbours the
+    /// way a fixed `search_width` in bins does. `num_harmonics`, `search_width`
+    /// and `resolve_harmonic_collisions` only apply to `Fft`, and the
+    /// tuning estimate is not updated under `ConstantQ`.
+    pub fn transform(mut self, kind: TransformKind) -> Self {
+        self.transform = kind;
+        self
+    }
+
+    /// Update the spectrum sample by sample with a sliding DFT instead of
+    /// running a full FFT on every emission (default false).
+    ///
+    /// Only the bins read by the chromagram are tracked, and the window is
+    /// applied in the frequency domain (as its periodic form, which differs
+    /// from the FFT path by well under 0.1% at the default buffer size). The
+    /// cost is per input sample rather than per emission, so this pays off
+    /// at high overlap, i.e. when emissions are frequent relative to the
+    /// buffer length. Bins outside the chromagram's search windows read as
+    /// zero.
+    #[cfg(feature = "sliding-dft")]
+    pub fn incremental_spectrum(mut self, enabled: bool) -> Self {
+        self.incremental_spectrum = enabled;
+        self
+    }
+
+    /// Set the per-emission decay of held notes (0.0..1.0, default 0.0 = off).
+    ///
+    /// Each output bin becomes `max(new, previous * carryover)`, a release
+    /// envelope on the chroma itself, so a sustained note that dips at a
+    /// window edge fades out instead of dropping for one emission. Unlike a
+    /// spectral peak-hold this operates on the final 12-bin chroma between
+    /// successive emissions.
+    pub fn note_carryover(mut self, carryover: f32) -> Self {
+        self.note_carryover = carryover;
+        self
+    }
+
+    /// Split spectral peaks shared by several pitch classes instead of
+    /// crediting each of them in full (default false).
+    ///
+    /// When harmonics of different notes fall on the same peak bin (e.g. the
+    /// third harmonic of C and the fundamental of G), every claiming pitch
+    /// class normally receives the whole peak. With this enabled the peak is
+    /// divided evenly among the distinct pitch classes that claim it. Claims
+    /// from the same pitch class (such as octave 2's fundamental and octave
+    /// 1's second harmonic) are not treated as collisions.
+    pub fn resolve_harmonic_collisions(mut self, resolve: bool) -> Self {
+        self.resolve_harmonic_collisions = resolve;
+        self
+    }
+
+    /// Refine every spectral peak by parabolic interpolation before folding
+    /// it into its pitch class (default false).
+    ///
+    /// The peak bin found for each note and harmonic is fitted with a
+    /// parabola through it and its two neighbours (on log magnitude), which
+    /// recovers the true peak height between bins and its frequency. A peak
+    /// whose refined frequency lies more than half a semitone from the
+    /// note, or a search window holding only the slope of a peak outside
+    /// it, then contributes nothing. Low notes, whose semitones span few
+    /// bins so that neighbouring search windows overlap, are separated much
+    /// better, at the cost of a few logarithms per peak. Only applies to
+    /// `TransformKind::Fft`.
+    pub fn peak_interpolation(mut self, enabled: bool) -> Self {
+        self.peak_interpolation = enabled;
+        self
+    }
+
+    /// Median-filter each magnitude bin over recent spectra before folding
+    /// (default false).
+    ///
+    /// Sustained tones keep their level from one emission to the next, so
+    /// the median leaves them alone, while a drum hit or other broadband
+    /// transient raises every bin only for the emissions whose window
+    /// contains it and is voted out. This is the harmonic half of a
+    /// median-filtering harmonic/percussive separation. A transient shows up
+    /// in `k = latency_samples / hop_size` (rounded up) overlapping windows,
+    /// 8 with the defaults, so the filter spans `2k + 1` spectra to outvote
+    /// them, at a memory cost of that many spectra. `k` may be at most 8:
+    /// `validate` rejects a `hop_size` below `latency_samples / 8`.
+    ///
+    /// The filter runs over past spectra only, so its output lags by half
+    /// its length: an added latency of `k * hop_size` input samples, at
+    /// least one more `latency_samples` (32768 input samples with the
+    /// defaults). `magnitude_spectrum` and `spectral_flux`
+    /// still report the unfiltered spectrum, and the first emissions after
+    /// a start or `reset` use the spectra seen so far.
+    pub fn harmonic_emphasis(mut self, enabled: bool) -> Self {
+        self.harmonic_emphasis = enabled;
+        self
+    }
+
+    /// Set the weight of each new chromagram in an exponential moving
+    /// average over emissions (0.0 < alpha <= 1.0, default 1.0 = off).
+    ///
+    /// Every emitted chroma becomes `alpha * new + (1 - alpha) * previous`,
+    /// where `previous` is the last emitted (already smoothed) chroma; the
+    /// first emission is passed through. Smaller values follow changes more
+    /// slowly but average out noise. Applied after `note_carryover`.
+    pub fn smoothing_alpha(mut self, alpha: f32) -> Self {
+        self.smoothing_alpha = alpha;
+        self
+    }
+
+    /// Skip frame-length validation in [`Chromagram::next_unchecked`]
+    /// (default false, which makes it panic on a wrongly sized frame).
+    ///
+    /// **Caution:** with this enabled a wrongly sized frame is never
+    /// reported; it yields wrong chromagrams or panics. The checked
+    /// [`Chromagram::next`] is unaffected.
+    pub fn unchecked(mut self, unchecked: bool) -> Self {
+        self.unchecked = unchecked;
+        self
+    }
+
+    /// Check every setting without building anything.
+    ///
+    /// `build` and `build_for` run this first. The first invalid setting is
+    /// reported as `ChromagramError::Configuration`, with a message naming
+    /// the field and the value it was given.
+    pub fn validate(&self) -> Result<(), ChromagramError> {
+        let invalid = |msg: String| Err(ChromagramError::Configuration(msg));
+        for (field, value) in [
+            ("frame_size", self.frame_size),
+            ("sampling_rate", self.sampling_rate),
+            ("input_rate", self.input_rate.unwrap_or(self.sampling_rate)),
+            ("downsample_factor", self.downsample_factor),
+            ("num_harmonics", self.num_harmonics),
+            ("num_octaves", self.num_octaves),
+        ] {
+            if value == 0 {
+                return invalid(format!("{field} must be positive, got 0"));
+            }
+        }
+        if !self.buffer_size.is_power_of_two() {
+            return invalid(format!("buffer_size must be a power of two, got {}", self.buffer_size));
+        }
+        if !self.buffer_size.is_multiple_of(self.downsample_factor) {
+            return invalid(format!(
+                "buffer_size ({}) must be divisible by downsample_factor ({})",
+                self.buffer_size, self.downsample_factor
+            ));
+        }
+        if !self.frame_size.is_multiple_of(self.downsample_factor) {
+            return invalid(format!(
+                "frame_size ({}) must be divisible by downsample_factor ({})",
+                self.frame_size, self.downsample_factor
+            ));
+        }
+        let hop = self.hop_size.unwrap_or(self.buffer_size / 2);
+        if !(1..=self.buffer_size).contains(&hop) {
+            return invalid(format!(
+                "hop_size must be in 1..={} (buffer_size), got {hop}",
+                self.buffer_size
             ));
         }
-        if self.frame_size == 0 {
-            return Err(ChromagramError::Configuration("frame_size cannot be zero".into()));
+        let window = self.buffer_size * self.downsample_factor;
+        if self.harmonic_emphasis && 2 * window.div_ceil(hop) + 1 > MAX_MEDIAN_SPECTRA {
+            return invalid(format!(
+                "hop_size must be at least {} with harmonic_emphasis, got {hop}",
+                window.div_ceil(MAX_MEDIAN_SPECTRA / 2)
+            ));
+        }
+        if !(0.0..1.0).contains(&self.note_carryover) {
+            return invalid(format!(
+                "note_carryover must be in 0.0..1.0, got {}",
+                self.note_carryover
+            ));
+        }
+        if !(self.harmonic_rolloff >= 0.0 && self.harmonic_rolloff.is_finite()) {
+            return invalid(format!(
+                "harmonic_rolloff must be a non-negative number, got {}",
+                self.harmonic_rolloff
+            ));
+        }
+        if !(self.smoothing_alpha > 0.0 && self.smoothing_alpha <= 1.0) {
+            return invalid(format!(
+                "smoothing_alpha must be in (0.0, 1.0], got {}",
+                self.smoothing_alpha
+            ));
+        }
+        if !(self.onset_threshold > 0.0 && self.onset_threshold <= 1.0) {
+            return invalid(format!(
+                "onset_threshold must be in (0.0, 1.0], got {}",
+                self.onset_threshold
+            ));
+        }
+        if !(TUNING_RANGE_HZ.0..=TUNING_RANGE_HZ.1).contains(&self.tuning_hz) {
+            return invalid(format!(
+                "tuning_hz must be in {}..={}, got {}",
+                TUNING_RANGE_HZ.0, TUNING_RANGE_HZ.1, self.tuning_hz
+            ));
+        }
+        if let Some(weights) = &self.octave_weights {
+            if weights.len() != self.num_octaves {
+                return invalid(format!(
+                    "octave_weights must have num_octaves ({}) entries, got {}",
+                    self.num_octaves,
+                    weights.len()
+                ));
+            }
+            if let Some(bad) = weights.iter().find(|w| !(**w >= 0.0 && w.is_finite())) {
+                return invalid(format!("octave_weights must be non-negative numbers, got {bad}"));
+            }
         }
+        Ok(())
+    }
+
+    /// Finalize and create the Chromagram.
+    pub fn build(self) -> Result<Chromagram, ChromagramError> {
+        self.build_for::<f32>()
+    }
+
+    /// Finalize and create a Chromagram that takes samples of type `T`,
+    /// e.g. `build_for::<f64>()` to keep an `f64` pipeline at full
+    /// precision up to the magnitude spectrum.
+    pub fn build_for<T: Sample>(self) -> Result<Chromagram<T>, ChromagramError> {
+        self.validate()?;
+        let octave_weights = match self.octave_weights {
+            Some(weights) => weights,
+            None => vec![1.0; self.num_octaves],
+        };
+        let hop_size = self.hop_size.unwrap_or(self.buffer_size / 2);
 
         // Prepare FFT plan once
-        let mut planner = FftPlanner::<f32>::new();
-        let fft = planner.plan_fft_forward(BUFFER_SIZE);
+        let mut planner = RealFftPlanner::<T>::new();
+        let fft = planner.plan_fft_forward(self.buffer_size);
+        let fft_scratch = fft.make_scratch_vec();
 
-        // Precompute pitch-class reference frequencies (C = 130.81Hz)
+        // Precompute pitch-class reference frequencies (C3, nine semitones
+        // below A3 = A4 / 2; 130.81Hz at A4 = 440Hz)
         let mut note_frequencies = [0.0; SEMITONES];
-        let reference = 130.8127f32;
+        let reference = self.tuning_hz / 2f32.powf(9.0 / 12.0) / 2.0;
         for (i, freq) in note_frequencies.iter_mut().enumerate() {
             *freq = reference * 2f32.powf(i as f32 / 12.0);
         }
 
-        Ok(Chromagram {
-            buffer: vec![0.0; BUFFER_SIZE],
+        let bin_width = (self.sampling_rate as f32 / self.downsample_factor as f32)
+            / self.buffer_size as f32;
+        let loudness_weights = if self.equal_loudness {
+            (0..=self.buffer_size / 2)
+                .map(|k| a_weighting(k as f32 * bin_width))
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let cq_bands = match self.transform {
+            TransformKind::Fft => Vec::new(),
+            TransformKind::ConstantQ => constant_q_bands(
+                &note_frequencies,
+                self.num_octaves,
+                bin_width,
+                self.buffer_size / 2,
+            ),
+        };
+
+        #[allow(unused_mut)]
+        let mut chromagram = Chromagram {
+            buffer: vec![T::zero(); self.buffer_size],
             head: 0,
-            filtered: vec![0.0; self.frame_size / self.downsample_factor],
-            fft_buffer: vec![Complex { re: 0.0, im: 0.0 }; BUFFER_SIZE],
-            magnitude: vec![0.0; (BUFFER_SIZE / 2) + 1],
+            filtered: vec![T::zero(); self.frame_size / self.downsample_factor],
+            mono: vec![T::zero(); self.frame_size],
+            pending: Vec::with_capacity(self.frame_size),
+            resampler: self
+                .input_rate
+                .filter(|&rate| rate != self.sampling_rate)
+                .map(|rate| Resampler::new(rate, self.sampling_rate)),
+            resampled: Vec::new(),
+            decimation_phase: 0,
+            window: self.window.coefficients(self.buffer_size).into_iter().map(lift).collect(),
+            fft_input: vec![T::zero(); self.buffer_size],
+            fft_output: vec![Complex::new(T::zero(), T::zero()); (self.buffer_size / 2) + 1],
+            fft_scratch,
+            magnitude: vec![0.0; (self.buffer_size / 2) + 1],
+            prev_magnitude: vec![0.0; (self.buffer_size / 2) + 1],
+            spectral_flux: 0.0,
+            onset: false,
+            onset_threshold: self.onset_threshold,
+            antialias: self.antialias && self.downsample_factor > 1,
+            biquads: Biquad::anti_alias(self.downsample_factor, self.decimation_quality.stages()),
+            filter_stages: self.decimation_quality.stages(),
+            loudness_weights,
+            transform: self.transform,
+            cq_bands,
+            normalize: self.normalize,
             chroma: [0.0; SEMITONES],
+            carryover: [0.0; SEMITONES],
+            note_carryover: self.note_carryover,
+            resolve_collisions: self.resolve_harmonic_collisions,
+            peak_interpolation: self.peak_interpolation,
+            recent_spectra: if self.harmonic_emphasis {
+                let overlapping = (self.buffer_size * self.downsample_factor).div_ceil(hop_size);
+                vec![vec![0.0; (self.buffer_size / 2) + 1]; 2 * overlapping + 1]
+            } else {
+                Vec::new()
+            },
+            median_scratch: Vec::new(),
+            spectra_seen: 0,
+            unchecked: self.unchecked,
+            smoothing_alpha: self.smoothing_alpha,
+            smoothed: None,
+            peak_bins: vec![0; SEMITONES * self.num_octaves * self.num_harmonics],
+            peak_values: vec![0.0; SEMITONES * self.num_octaves * self.num_harmonics],
+            octave_chroma: vec![[0.0; SEMITONES]; self.num_octaves],
+            octave_weights,
+            harmonic_weights: (1..=self.num_harmonics)
+                .map(|h| 1.0 / (h as f32).powf(self.harmonic_rolloff))
+                .collect(),
+            bin_owners: vec![0; (self.buffer_size / 2) + 1],
+            buffer_size: self.buffer_size,
+            hop_size,
             sampling_rate: self.sampling_rate,
             frame_size: self.frame_size,
             downsample_factor: self.downsample_factor,
@@ -134,9 +900,24 @@ impl ChromagramBuilder {
             num_octaves: self.num_octaves,
             search_width: self.search_width,
             samples_since_last: 0,
+            samples_pushed: 0,
+            emitted_at: 0,
+            tuning_cents: 0.0,
+            tuning_ready: false,
             fft,
             note_frequencies,
-        })
+            #[cfg(feature = "sliding-dft")]
+            sliding: None,
+        };
+
+        #[cfg(feature = "sliding-dft")]
+        if self.incremental_spectrum {
+            let bins = chromagram.tracked_bins();
+            chromagram.sliding =
+                Some(SlidingDft::new(self.buffer_size, bins, self.window.cosine_terms()));
+        }
+
+        Ok(chromagram)
     }
 }
 
@@ -147,13 +928,72 @@ impl Default for ChromagramBuilder {
 }
 
 /// Streaming chromagram calculator.
-pub struct Chromagram {
-    buffer: Vec<f32>,
+///
+/// Generic over the input [`Sample`] type, `f32` by default; build an `f64`
+/// one with [`ChromagramBuilder::build_for`].
+///
+/// Cloning is cheaper than building: the clone shares the FFT plan through
+/// an `Arc` but gets its own copy of every buffer and of the streaming state
+/// (buffered samples, hop position, smoothing, tuning and onset history), so
+/// the two continue independently from the point of the clone.
+#[derive(Clone)]
+pub struct Chromagram<T: Sample = f32> {
+    buffer: Vec<T>,
     head: usize,
-    filtered: Vec<f32>,
-    fft_buffer: Vec<Complex<f32>>,
+    filtered: Vec<T>,
+    /// Downmix of the last interleaved frame
+    mono: Vec<T>,
+    /// Samples passed to `push` that do not yet fill a frame
+    pending: Vec<T>,
+    /// Converts input frames to `sampling_rate`; `None` when the rates match
+    resampler: Option<Resampler<T>>,
+    /// The last input frame at `sampling_rate`
+    resampled: Vec<T>,
+    /// Position within the decimation step of the next resampled sample
+    decimation_phase: usize,
+    window: Vec<T>,
+    fft_input: Vec<T>,
+    fft_output: Vec<Complex<T>>,
+    fft_scratch: Vec<Complex<T>>,
     magnitude: Vec<f32>,
+    prev_magnitude: Vec<f32>,
+    spectral_flux: f32,
+    onset: bool,
+    onset_threshold: f32,
+    /// Run the biquads before decimating; off at `downsample_factor` 1
+    antialias: bool,
+    /// Anti-alias cascade, of which the first `filter_stages` sections run
+    biquads: [Biquad<T>; MAX_FILTER_STAGES],
+    filter_stages: usize,
+    loudness_weights: Vec<f32>,
+    transform: TransformKind,
+    /// Constant-Q bands, octave-major (`octave * 12 + note`)
+    cq_bands: Vec<CqBand>,
+    normalize: NormKind,
     chroma: [f32; SEMITONES],
+    carryover: [f32; SEMITONES],
+    note_carryover: f32,
+    resolve_collisions: bool,
+    peak_interpolation: bool,
+    /// Last magnitude spectra for `harmonic_emphasis`, oldest overwritten
+    /// first; empty when it is off
+    recent_spectra: Vec<Vec<f32>>,
+    /// One bin of every stored spectrum, sorted for the median
+    median_scratch: Vec<f32>,
+    /// Spectra written to `recent_spectra` since the start or `reset`
+    spectra_seen: usize,
+    unchecked: bool,
+    smoothing_alpha: f32,
+    smoothed: Option<[f32; SEMITONES]>,
+    peak_bins: Vec<usize>,
+    /// Magnitude credited to each entry of `peak_bins`
+    peak_values: Vec<f32>,
+    bin_owners: Vec<u16>,
+    buffer_size: usize,
+    hop_size: usize,
+    octave_chroma: Vec<[f32; SEMITONES]>,
+    octave_weights: Vec<f32>,
+    harmonic_weights: Vec<f32>,
     sampling_rate: usize,
     frame_size: usize,
     downsample_factor: usize,
@@ -161,8 +1001,16 @@ pub struct Chromagram {
     num_octaves: usize,
     search_width: usize,
     samples_since_last: usize,
-    fft: Arc<dyn Fft<f32>>,
+    /// Input samples consumed since the start of the stream
+    samples_pushed: u64,
+    /// `samples_pushed` at the most recent emission
+    emitted_at: u64,
+    tuning_cents: f32,
+    tuning_ready: bool,
+    fft: Arc<dyn RealToComplex<T>>,
     note_frequencies: [f32; SEMITONES],
+    #[cfg(feature = "sliding-dft")]
+    sliding: Option<SlidingDft>,
 }
 
 impl Chromagram {
@@ -170,127 +1018,952 @@ impl Chromagram {
     pub fn builder() -> ChromagramBuilder {
         ChromagramBuilder::new()
     }
+}
+
+impl<T: Sample> Chromagram<T> {
+    /// Number of samples expected by each call to `next`.
+    pub fn frame_size(&self) -> usize {
+        self.frame_size
+    }
+
+    /// Inherent analysis latency in input samples: `buffer_size *
+    /// downsample_factor`, the span of audio covered by one chromagram.
+    ///
+    /// A note must have filled the whole analysis window before it is fully
+    /// represented, so divide by `sampling_rate` for the delay in seconds
+    /// (about 0.74 s with the defaults).
+    pub fn latency_samples(&self) -> usize {
+        self.buffer_size * self.downsample_factor
+    }
+
+    /// Input samples still to push before the next chromagram is emitted.
+    ///
+    /// Starts at `hop_size`, falls by `frame_size` with every frame that
+    /// does not emit, and starts over after an emission. Pushing
+    /// `samples_until_next().div_ceil(frame_size)` more frames is enough to
+    /// produce a chromagram; divide by `sampling_rate` for the wait in
+    /// seconds.
+    pub fn samples_until_next(&self) -> usize {
+        self.hop_size - self.samples_since_last
+    }
+
+    /// Whether a full analysis window has been pushed since the stream
+    /// started (or since `reset`).
+    ///
+    /// The buffer starts out zeroed, and the first chromagrams are emitted
+    /// after only `hop_size` samples, so until `latency_samples()` input
+    /// samples have arrived part of every window is silence and notes read
+    /// weaker than they are. Callers that need steady levels can discard
+    /// chromagrams while this is false; it turns true on the first frame
+    /// that fills the buffer and stays true until `reset`.
+    pub fn is_primed(&self) -> bool {
+        self.samples_pushed >= self.latency_samples() as u64
+    }
+
+    /// Input sample position of the most recent chromagram: the center of
+    /// its analysis window, counted from the first sample pushed since the
+    /// stream started (or since `reset`).
+    ///
+    /// A chromagram describes the `latency_samples()` input samples ending
+    /// at the newest one pushed when it was emitted, so the center lies half
+    /// that span before the emission point; this makes the position line
+    /// up with the audio that was analyzed rather than with when the result
+    /// became available. Early emissions, whose window still reaches back
+    /// before the stream began, report 0. Also 0 before the first emission.
+    /// [`Chromagram::chroma_from_magnitude`] does not move it.
+    pub fn last_frame_sample(&self) -> u64 {
+        self.emitted_at.saturating_sub(self.latency_samples() as u64 / 2)
+    }
+
+    /// [`Chromagram::last_frame_sample`] in seconds at `sampling_rate`.
+    pub fn last_frame_time(&self) -> f64 {
+        self.last_frame_sample() as f64 / self.sampling_rate as f64
+    }
+
+    /// Running estimate of how far the input is tuned from the reference, in
+    /// cents (positive = sharp).
+    ///
+    /// Updated on every emission from the interpolated positions of the
+    /// fundamental peaks, weighted by their energy. The first tonal emission
+    /// seeds the estimate; afterwards each emission moves it 10% of the way
+    /// towards the new measurement, so allow roughly 20 emissions of tonal
+    /// material before trusting it. Returns 0.0 until a tonal emission.
+    pub fn current_tuning_cents(&self) -> f32 {
+        self.tuning_cents
+    }
+
+    /// Per-octave contributions to the most recent chromagram.
+    ///
+    /// Returns one 12-bin array per analyzed octave, lowest first; summing
+    /// them gives the chroma returned by the last `Some` from `next`, before
+    /// `normalize` is applied. The
+    /// values are overwritten on every emission (and are all zero before the
+    /// first one).
+    pub fn octave_energies(&self) -> Vec<[f32; SEMITONES]> {
+        self.octave_chroma.clone()
+    }
+
+    /// Linear magnitude spectrum behind the most recent chromagram.
+    ///
+    /// Holds `buffer_size / 2 + 1` bins, from DC to the Nyquist frequency of
+    /// the downsampled signal (with `equal_loudness` applied, if enabled).
+    /// Only meaningful after `next` or `flush` returned `Some`; it is
+    /// overwritten by the next emission. With `incremental_spectrum` only
+    /// the bins the chromagram reads are filled in.
+    pub fn magnitude_spectrum(&self) -> &[f32] {
+        &self.magnitude
+    }
+
+    /// Normalized spectral flux of the most recent emission, in 0.0..=1.0.
+    ///
+    /// Flux is the sum of the positive bin-to-bin magnitude increases from
+    /// the previous emission's spectrum to this one, divided here by the
+    /// total magnitude of this one so it does not depend on level: 1.0
+    /// after silence, near 0.0 on a sustained sound. Zero for a silent
+    /// spectrum and before the first emission.
+    pub fn spectral_flux(&self) -> f32 {
+        self.spectral_flux
+    }
+
+    /// Whether the most recent emission starts a new onset.
+    ///
+    /// True when [`Chromagram::spectral_flux`] rises to the configured
+    /// `onset_threshold` from below it on the previous emission. Flux stays
+    /// high while a new sound fills the analysis window, so requiring the
+    /// rising edge reports each onset once rather than on every emission
+    /// until the window is full.
+    pub fn onset_detected(&self) -> bool {
+        self.onset
+    }
+
+    /// Center frequency in Hz of spectrum bin `bin`.
+    pub fn bin_frequency(&self, bin: usize) -> f32 {
+        bin as f32 * self.bin_width()
+    }
 
     /// Push one audio frame in. Returns `Ok(None)` until enough data accumulates,
     /// then `Ok(Some(chroma))` when a new chromagram is ready.
-    pub fn next(&mut self, frame: &[f32]) -> Result<Option<[f32; SEMITONES]>, ChromagramError> {
+    ///
+    /// At most one chromagram is returned per frame: if the frame crosses
+    /// several hop boundaries, only the analysis at its end is reported. Use
+    /// [`Chromagram::next_all`] when frames may be longer than the hop.
+    pub fn next(&mut self, frame: &[T]) -> Result<Option<[f32; SEMITONES]>, ChromagramError> {
+        if frame.len() != self.frame_size {
+            return Err(ChromagramError::InvalidFrameSize {
+                expected: self.frame_size,
+                got: frame.len(),
+            });
+        }
+        Ok(self.process_frame(frame))
+    }
+
+    /// Push one audio frame in like [`Chromagram::next`], but return the
+    /// chromagram split by octave.
+    ///
+    /// Each emission yields `num_octaves` rows, lowest octave first (row 0
+    /// starts at C3 with the default tuning), holding what that octave
+    /// contributed to each pitch class with `octave_weights` applied. The
+    /// rows are taken before the octaves are summed, so they sum to the
+    /// chroma `next` would return only when `normalize`, `note_carryover`
+    /// and `smoothing_alpha` are off; those act on the folded chroma alone.
+    /// The streaming state advances exactly as with `next`, and the rows
+    /// stay readable through [`Chromagram::octave_energies`].
+    pub fn next_multi_octave(
+        &mut self,
+        frame: &[T],
+    ) -> Result<Option<Vec<[f32; SEMITONES]>>, ChromagramError> {
+        Ok(self.next(frame)?.map(|_| self.octave_chroma.clone()))
+    }
+
+    /// Push one interleaved multichannel frame in, averaging the channels
+    /// to mono before analysis.
+    ///
+    /// `frame` holds `frame_size` sample groups of `channels` samples each
+    /// (`L R L R ...` for stereo), so its length must be `frame_size *
+    /// channels`. Otherwise behaves exactly like [`Chromagram::next`] on the
+    /// downmixed frame; a single channel is passed through unchanged.
+    pub fn next_interleaved(
+        &mut self,
+        frame: &[T],
+        channels: usize,
+    ) -> Result<Option<[f32; SEMITONES]>, ChromagramError> {
+        if channels == 0 {
+            return Err(ChromagramError::InvalidChannels);
+        }
+        if frame.len() != self.frame_size * channels {
+            return Err(ChromagramError::InvalidFrameSize {
+                expected: self.frame_size * channels,
+                got: frame.len(),
+            });
+        }
+
+        let scale = T::one() / T::from_usize(channels).expect("channel count fits the sample type");
+        let mut mono = core::mem::take(&mut self.mono);
+        for (out, group) in mono.iter_mut().zip(frame.chunks_exact(channels)) {
+            *out = group.iter().fold(T::zero(), |sum, &s| sum + s) * scale;
+        }
+        let chroma = self.process_frame(&mono);
+        self.mono = mono;
+        Ok(chroma)
+    }
+
+    /// Push one audio frame in and return every chromagram it completes.
+    ///
+    /// The frame is written to the buffer sample by sample and a chromagram
+    /// is computed at each hop boundary it crosses, oldest first, so the
+    /// output does not depend on how the caller chunks its audio relative
+    /// to `hop_size`. Returns an empty `Vec` when no boundary is crossed.
+    /// Shares its hop counter with `next`, so the two can be mixed.
+    pub fn next_all(&mut self, frame: &[T]) -> Result<Vec<[f32; SEMITONES]>, ChromagramError> {
         if frame.len() != self.frame_size {
             return Err(ChromagramError::InvalidFrameSize {
                 expected: self.frame_size,
                 got: frame.len(),
             });
         }
+        let mut emitted = Vec::new();
+        self.process_frame_all(frame, &mut emitted);
+        Ok(emitted)
+    }
 
+    /// Push any number of samples in and return every chromagram they
+    /// complete.
+    ///
+    /// For sources that deliver blocks of varying size: samples are
+    /// gathered into `frame_size` frames, and each complete frame is
+    /// processed as by [`Chromagram::next_all`], so a chromagram is
+    /// produced at every hop boundary. Up to `frame_size - 1` samples that
+    /// do not fill a frame are kept for the next call, or analyzed by
+    /// `flush` (`reset` discards them). The output therefore depends
+    /// only on the samples pushed, not on how they were split between
+    /// calls. Avoid calling `next` while samples are held back, as its
+    /// frame would be analyzed ahead of them.
+    pub fn push(&mut self, samples: &[T]) -> Vec<[f32; SEMITONES]> {
+        let mut emitted = Vec::new();
+        let mut pending = core::mem::take(&mut self.pending);
+        let mut rest = samples;
+
+        if !pending.is_empty() {
+            let take = (self.frame_size - pending.len()).min(rest.len());
+            pending.extend_from_slice(&rest[..take]);
+            rest = &rest[take..];
+            if pending.len() == self.frame_size {
+                self.process_frame_all(&pending, &mut emitted);
+                pending.clear();
+            }
+        }
+        let mut frames = rest.chunks_exact(self.frame_size);
+        for frame in frames.by_ref() {
+            self.process_frame_all(frame, &mut emitted);
+        }
+        pending.extend_from_slice(frames.remainder());
+
+        self.pending = pending;
+        emitted
+    }
+
+    /// Buffer one frame and append a chromagram to `emitted` for every hop
+    /// boundary it crosses.
+    fn process_frame_all(&mut self, frame: &[T], emitted: &mut Vec<[f32; SEMITONES]>) {
+        if self.resampler.is_some() {
+            self.push_resampled(frame, Some(emitted));
+            return;
+        }
+        self.downsample_frame(frame);
+
+        for i in 0..self.filtered.len() {
+            self.push_sample(self.filtered[i]);
+            self.samples_pushed += self.downsample_factor as u64;
+            self.samples_since_last += self.downsample_factor;
+            if self.samples_since_last >= self.hop_size {
+                self.samples_since_last %= self.hop_size;
+                emitted.push(self.emit());
+            }
+        }
+    }
+
+    /// Push one audio frame in without returning a `Result`.
+    ///
+    /// By default a frame whose length differs from `frame_size` panics.
+    /// On a chromagram built with `unchecked(true)` the length is **not
+    /// validated at all**: a wrongly sized frame then silently produces
+    /// wrong chromagrams (the hop counter and decimation drift) or panics
+    /// on an out-of-bounds write. Only enable that when frame sizes are
+    /// guaranteed upstream.
+    ///
+    /// # Panics
+    ///
+    /// If `frame.len() != frame_size` and `unchecked` is off.
+    pub fn next_unchecked(&mut self, frame: &[T]) -> Option<[f32; SEMITONES]> {
+        if !self.unchecked {
+            assert_eq!(frame.len(), self.frame_size, "invalid frame size");
+        }
+        self.process_frame(frame)
+    }
+
+    /// Fold a precomputed magnitude spectrum into a chromagram, skipping the
+    /// buffer, window and FFT.
+    ///
+    /// `magnitude` must hold `buffer_size / 2 + 1` linear magnitudes from DC
+    /// to the Nyquist frequency, like [`Chromagram::magnitude_spectrum`].
+    /// Bin frequencies still derive from the configured `sampling_rate`,
+    /// `downsample_factor` and `buffer_size`, so the spectrum must come from
+    /// an FFT of that length at that rate. It is taken as is: `equal_loudness`
+    /// is not applied to it. Everything downstream of the spectrum runs as in
+    /// `next` (tuning, onset, normalization, carryover and smoothing state is
+    /// updated), but the sample buffer and hop counter are left untouched.
+    pub fn chroma_from_magnitude(
+        &mut self,
+        magnitude: &[f32],
+    ) -> Result<[f32; SEMITONES], ChromagramError> {
+        if magnitude.len() != self.magnitude.len() {
+            return Err(ChromagramError::InvalidSpectrumSize {
+                expected: self.magnitude.len(),
+                got: magnitude.len(),
+            });
+        }
+        self.magnitude.copy_from_slice(magnitude);
+        Ok(self.fold_spectrum())
+    }
+
+    /// Buffer one frame, or a shorter final block from `flush`, and compute
+    /// a chromagram when the hop is reached.
+    #[inline]
+    fn process_frame(&mut self, frame: &[T]) -> Option<[f32; SEMITONES]> {
+        if self.resampler.is_some() {
+            return self.push_resampled(frame, None).then(|| self.emit());
+        }
         self.downsample_frame(frame);
 
         // Write filtered samples into circular buffer
-        for &s in &self.filtered {
-            self.buffer[self.head] = s;
-            self.head = (self.head + 1) % BUFFER_SIZE;
+        for i in 0..frame.len().div_ceil(self.downsample_factor) {
+            self.push_sample(self.filtered[i]);
         }
 
-        self.samples_since_last += self.frame_size;
-        if self.samples_since_last < CHROMA_INTERVAL {
-            return Ok(None);
+        self.samples_pushed += frame.len() as u64;
+        self.samples_since_last += frame.len();
+        if self.samples_since_last < self.hop_size {
+            return None;
         }
-        self.samples_since_last -= CHROMA_INTERVAL;
+        // A frame longer than the hop may cross several boundaries; they
+        // all end at the same newest sample, so one emission covers them
+        self.samples_since_last %= self.hop_size;
+        Some(self.emit())
+    }
+
+    /// Resample one input frame to `sampling_rate`, then filter, decimate
+    /// and buffer it sample by sample, since a resampled frame need not be
+    /// a whole number of decimation steps.
+    ///
+    /// With `emitted`, a chromagram is computed at every hop boundary, as
+    /// in `next_all`. Returns whether any boundary was crossed.
+    fn push_resampled(
+        &mut self,
+        frame: &[T],
+        mut emitted: Option<&mut Vec<[f32; SEMITONES]>>,
+    ) -> bool {
+        let mut resampled = core::mem::take(&mut self.resampled);
+        resampled.clear();
+        if let Some(resampler) = &mut self.resampler {
+            resampler.process(frame, &mut resampled);
+        }
+
+        let mut crossed = false;
+        for &x in &resampled {
+            let y = if self.antialias {
+                let stages = &mut self.biquads[..self.filter_stages];
+                stages.iter_mut().fold(x, |x, biquad| biquad.process(x))
+            } else {
+                x
+            };
+            if self.decimation_phase == 0 {
+                self.push_sample(y);
+            }
+            self.decimation_phase = (self.decimation_phase + 1) % self.downsample_factor;
+
+            self.samples_pushed += 1;
+            self.samples_since_last += 1;
+            if self.samples_since_last >= self.hop_size {
+                self.samples_since_last = 0;
+                crossed = true;
+                if let Some(emitted) = emitted.as_deref_mut() {
+                    emitted.push(self.emit());
+                }
+            }
+        }
+        self.resampled = resampled;
+        crossed
+    }
+
+    /// Write one downsampled sample into the circular buffer.
+    #[inline]
+    fn push_sample(&mut self, sample: T) {
+        #[cfg(feature = "sliding-dft")]
+        if let Some(sliding) = &mut self.sliding {
+            sliding.push(sample.to_f64(), self.buffer[self.head].to_f64());
+        }
+        self.buffer[self.head] = sample;
+        self.head = (self.head + 1) % self.buffer_size;
+    }
+
+    /// Force a chromagram from whatever is buffered, e.g. at end of stream.
+    ///
+    /// Samples `push` is still holding back for an incomplete frame are
+    /// buffered first. Returns `None` if no samples arrived since the last
+    /// emission. Otherwise the usual analysis window ending at the newest
+    /// sample is analyzed, so the tail of a recording is not lost even though
+    /// it never reached a full hop. Older parts of that window may repeat
+    /// audio already reported, or be zero if the stream was shorter than the
+    /// window.
+    pub fn flush(&mut self) -> Option<[f32; SEMITONES]> {
+        let mut pending = core::mem::take(&mut self.pending);
+        // Held-back samples that cross a hop emit here, ending at the newest sample
+        let emitted = if pending.is_empty() { None } else { self.process_frame(&pending) };
+        pending.clear();
+        self.pending = pending;
+
+        let chroma = match emitted {
+            Some(chroma) => chroma,
+            None if self.samples_since_last == 0 => return None,
+            None => self.emit(),
+        };
+        self.samples_since_last = 0;
+        Some(chroma)
+    }
+
+    /// Clear all streaming state so the next frame starts a fresh stream.
+    ///
+    /// Zeroes the sample buffer and every history carried between emissions
+    /// (carryover, smoothing, tuning estimate, onset state). The FFT plan, window and
+    /// precomputed `note_frequencies` are preserved, so this is much cheaper
+    /// than building a new instance.
+    pub fn reset(&mut self) {
+        self.buffer.fill(T::zero());
+        self.head = 0;
+        self.samples_since_last = 0;
+        self.samples_pushed = 0;
+        self.emitted_at = 0;
+        self.pending.clear();
+        self.filtered.fill(T::zero());
+        self.biquads.iter_mut().for_each(Biquad::reset);
+        if let Some(resampler) = &mut self.resampler {
+            resampler.reset();
+        }
+        self.decimation_phase = 0;
+        self.chroma = [0.0; SEMITONES];
+        self.carryover = [0.0; SEMITONES];
+        self.smoothed = None;
+        self.octave_chroma.fill([0.0; SEMITONES]);
+        self.tuning_cents = 0.0;
+        self.tuning_ready = false;
+        self.prev_magnitude.fill(0.0);
+        self.spectra_seen = 0;
+        self.spectral_flux = 0.0;
+        self.onset = false;
+        #[cfg(feature = "sliding-dft")]
+        if let Some(sliding) = &mut self.sliding {
+            sliding.reset();
+        }
+    }
 
+    /// Analyze the current buffer contents.
+    #[inline]
+    fn emit(&mut self) -> [f32; SEMITONES] {
+        self.emitted_at = self.samples_pushed;
         self.compute_spectrum();
-        self.compute_chromagram();
-        Ok(Some(self.chroma))
+        self.fold_spectrum()
     }
 
+    /// Turn the current magnitude spectrum into the emitted chroma.
     #[inline]
-    fn downsample_frame(&mut self, input: &[f32]) {
-        let (b0, b1, b2) = (0.2929, 0.5858, 0.2929);
-        let (a1, a2) = (-0.0, 0.1716);
-        let mut x1 = 0.0;
-        let mut x2 = 0.0;
-        let mut y1 = 0.0;
-        let mut y2 = 0.0;
-        let mut out = 0;
+    fn fold_spectrum(&mut self) -> [f32; SEMITONES] {
+        self.update_onset();
+        if self.recent_spectra.is_empty() {
+            self.compute_chromagram();
+        } else {
+            // Fold the median spectrum, then put the real one back for
+            // `magnitude_spectrum`
+            let current = self.emphasize_harmonics();
+            self.compute_chromagram();
+            self.magnitude.copy_from_slice(&self.recent_spectra[current]);
+        }
+        if self.normalize != NormKind::None {
+            self.apply_normalization();
+        }
+        if self.note_carryover > 0.0 {
+            self.apply_carryover();
+        }
+        if self.smoothing_alpha < 1.0 {
+            self.apply_smoothing();
+        }
+        self.chroma
+    }
 
-        for (i, &x0) in input.iter().enumerate() {
-            let y0 = b0 * x0 + b1 * x1 + b2 * x2 - a1 * y1 - a2 * y2;
-            x2 = x1; x1 = x0;
-            y2 = y1; y1 = y0;
+    /// Low-pass and decimate one frame into `filtered`.
+    ///
+    /// The filter state persists across frames, so a stream split into
+    /// frames is filtered exactly as if it were processed in one piece.
+    #[inline]
+    fn downsample_frame(&mut self, input: &[T]) {
+        if !self.antialias {
+            let decimated = input.iter().step_by(self.downsample_factor);
+            for (out, &x) in self.filtered.iter_mut().zip(decimated) {
+                *out = x;
+            }
+            return;
+        }
 
+        let mut biquads = self.biquads;
+        let stages = &mut biquads[..self.filter_stages];
+        let mut out = 0;
+        for (i, &x0) in input.iter().enumerate() {
+            let y0 = stages.iter_mut().fold(x0, |x, biquad| biquad.process(x));
             if i % self.downsample_factor == 0 {
                 self.filtered[out] = y0;
                 out += 1;
             }
         }
+        self.biquads = biquads;
     }
 
     #[inline]
     fn compute_spectrum(&mut self) {
-        // Unwrap buffer into FFT input with Hamming
-        let start = (self.head + BUFFER_SIZE - CHROMA_INTERVAL) % BUFFER_SIZE;
-        (0..BUFFER_SIZE).for_each(|i| {
-            let sample = self.buffer[(start + i) % BUFFER_SIZE];
-            self.fft_buffer[i].re = sample * HAMMING_WINDOW[i];
-            self.fft_buffer[i].im = 0.0;
+        #[cfg(feature = "sliding-dft")]
+        if let Some(sliding) = &self.sliding {
+            sliding.magnitudes(&mut self.magnitude);
+            for (mag, w) in self.magnitude.iter_mut().zip(&self.loudness_weights) {
+                *mag *= w;
+            }
+            return;
+        }
+
+        // Unwrap buffer into the real FFT input with the window applied,
+        // oldest sample first
+        let start = self.head;
+        (0..self.buffer_size).for_each(|i| {
+            let sample = self.buffer[(start + i) % self.buffer_size];
+            self.fft_input[i] = sample * self.window[i];
         });
 
-        self.fft.process(&mut self.fft_buffer);
+        // Lengths are fixed at build time, so this cannot fail
+        self.fft
+            .process_with_scratch(&mut self.fft_input, &mut self.fft_output, &mut self.fft_scratch)
+            .expect("FFT buffers sized at build time");
 
-        for (i, mag) in self.magnitude.iter_mut().enumerate() {
-            let c = &self.fft_buffer[i];
-            *mag = (c.re * c.re + c.im * c.im).sqrt();
+        for (mag, c) in self.magnitude.iter_mut().zip(&self.fft_output) {
+            *mag = (c.re * c.re + c.im * c.im).sqrt().to_f32();
+        }
+        for (mag, w) in self.magnitude.iter_mut().zip(&self.loudness_weights) {
+            *mag *= w;
         }
     }
 
+    /// Compare the fresh spectrum with the previous emission's for onsets.
     #[inline]
-    fn compute_chromagram(&mut self) {
-        let bin_width = (self.sampling_rate as f32 / self.downsample_factor as f32)
-            / BUFFER_SIZE as f32;
+    fn update_onset(&mut self) {
+        let mut rise = 0.0;
+        let mut total = 0.0;
+        for (&mag, prev) in self.magnitude.iter().zip(&mut self.prev_magnitude) {
+            rise += (mag - *prev).max(0.0);
+            total += mag;
+            *prev = mag;
+        }
+
+        let flux = if total > 0.0 { rise / total } else { 0.0 };
+        self.onset = flux >= self.onset_threshold && self.spectral_flux < self.onset_threshold;
+        self.spectral_flux = flux;
+    }
+
+    /// Store the current spectrum and replace `magnitude` with the per-bin
+    /// median of the stored ones. Returns the slot holding the original.
+    fn emphasize_harmonics(&mut self) -> usize {
+        let frames = self.recent_spectra.len();
+        let current = self.spectra_seen % frames;
+        self.recent_spectra[current].copy_from_slice(&self.magnitude);
+        self.spectra_seen += 1;
+
+        let stored = &self.recent_spectra[..self.spectra_seen.min(frames)];
+        let values = &mut self.median_scratch;
+        for (bin, mag) in self.magnitude.iter_mut().enumerate() {
+            values.clear();
+            values.extend(stored.iter().map(|spectrum| spectrum[bin]));
+            values.sort_unstable_by(f32::total_cmp);
+            // Lower median, so a fresh transient never wins a tie
+            *mag = values[(values.len() - 1) / 2];
+        }
+        current
+    }
+
+    /// Width of one FFT bin in Hz
+    #[inline]
+    fn bin_width(&self) -> f32 {
+        (self.sampling_rate as f32 / self.downsample_factor as f32) / self.buffer_size as f32
+    }
+
+    /// Inclusive range of bins searched for the peak of one harmonic
+    #[inline]
+    fn search_range(&self, note: usize, octave: usize, harm: usize) -> (usize, usize) {
         let max_bin = self.magnitude.len() - 1;
+        let freq = self.note_frequencies[note] * octave as f32 * harm as f32;
+        let center = (freq / self.bin_width()).round() as usize;
+        let lo = center.saturating_sub(self.search_width * harm).min(max_bin);
+        let hi = (center + self.search_width * harm).min(max_bin);
+        (lo, hi)
+    }
 
+    /// Every bin read by `compute_chromagram` and `update_tuning`, sorted
+    #[cfg(feature = "sliding-dft")]
+    fn tracked_bins(&self) -> Vec<usize> {
+        let max_bin = self.magnitude.len() - 1;
+        let mut tracked = vec![false; max_bin + 1];
+        for n in 0..SEMITONES {
+            for octave in 1..=self.num_octaves {
+                for harm in 1..=self.num_harmonics {
+                    let (lo, hi) = self.search_range(n, octave, harm);
+                    // one extra bin each side for peak interpolation
+                    tracked[lo.saturating_sub(1)..=(hi + 1).min(max_bin)].fill(true);
+                }
+            }
+        }
+        for band in &self.cq_bands {
+            tracked[band.start..band.start + band.weights.len()].fill(true);
+        }
+        (0..=max_bin).filter(|&k| tracked[k]).collect()
+    }
+
+    #[inline]
+    fn compute_chromagram(&mut self) {
+        if self.transform == TransformKind::ConstantQ {
+            self.fold_constant_q();
+            return;
+        }
+        let bin_width = self.bin_width();
+
+        // 1) locate the peak bin for every (note, octave, harmonic)
+        let mut k = 0;
         for n in 0..SEMITONES {
-            let mut c_sum = 0.0;
             for octave in 1..=self.num_octaves {
-                let mut note_sum = 0.0;
                 for harm in 1..=self.num_harmonics {
-                    let freq = self.note_frequencies[n] * octave as f32 * harm as f32;
-                    let center = (freq / bin_width).round() as usize;
-                    let lo = center.saturating_sub(self.search_width * harm);
-                    let hi = (center + self.search_width * harm).min(max_bin);
-
-                    let peak = self.magnitude[lo..=hi]
-                        .iter()
-                        .cloned()
-                        .fold(0.0_f32, f32::max);
-                    note_sum += peak / harm as f32;
+                    let (lo, hi) = self.search_range(n, octave, harm);
+
+                    let mut peak_bin = lo;
+                    for bin in lo..=hi {
+                        if self.magnitude[bin] > self.magnitude[peak_bin] {
+                            peak_bin = bin;
+                        }
+                    }
+                    let value = if self.peak_interpolation {
+                        let target = self.note_frequencies[n] * (octave * harm) as f32;
+                        self.interpolated_peak(peak_bin, target / bin_width)
+                    } else {
+                        self.magnitude[peak_bin]
+                    };
+                    self.peak_bins[k] = peak_bin;
+                    self.peak_values[k] = value;
+                    if self.resolve_collisions && value > 0.0 {
+                        self.bin_owners[peak_bin] |= 1 << n;
+                    }
+                    k += 1;
                 }
+            }
+        }
+
+        // 2) fold the weighted peaks into pitch classes
+        let mut k = 0;
+        for n in 0..SEMITONES {
+            let mut c_sum = 0.0;
+            for octave in 0..self.num_octaves {
+                let mut note_sum = 0.0;
+                for weight in &self.harmonic_weights {
+                    let peak_bin = self.peak_bins[k];
+                    let mut peak = self.peak_values[k];
+                    if self.resolve_collisions && peak > 0.0 {
+                        peak /= self.bin_owners[peak_bin].count_ones() as f32;
+                    }
+                    note_sum += peak * weight;
+                    k += 1;
+                }
+                note_sum *= self.octave_weights[octave];
+                self.octave_chroma[octave][n] = note_sum;
                 c_sum += note_sum;
             }
             self.chroma[n] = c_sum;
         }
+
+        if self.resolve_collisions {
+            for &peak_bin in &self.peak_bins {
+                self.bin_owners[peak_bin] = 0;
+            }
+        }
+
+        self.update_tuning(bin_width);
+    }
+
+    /// Height of the peak at `bin` refined by a parabola through it and its
+    /// neighbours, or zero if it is not a local maximum or its refined
+    /// position lies more than half a semitone from `target_bin`.
+    #[inline]
+    fn interpolated_peak(&self, bin: usize, target_bin: f32) -> f32 {
+        let b = self.magnitude[bin];
+        if bin == 0 || bin == self.magnitude.len() - 1 {
+            return b;
+        }
+        let (a, c) = (self.magnitude[bin - 1], self.magnitude[bin + 1]);
+        if b < a || b < c || b <= 0.0 {
+            return 0.0; // slope of a peak outside the search window
+        }
+
+        let (la, lb, lc) = ((a + 1e-9).ln(), (b + 1e-9).ln(), (c + 1e-9).ln());
+        let denom = la - 2.0 * lb + lc;
+        let delta = if denom < 0.0 { 0.5 * (la - lc) / denom } else { 0.0 };
+        let semitones = 12.0 * ((bin as f32 + delta) / target_bin).log2();
+        if semitones.abs() > 0.5 {
+            return 0.0;
+        }
+        (lb - 0.25 * (la - lc) * delta).exp()
+    }
+
+    /// Sum the magnitude spectrum through the constant-Q bands.
+    #[inline]
+    fn fold_constant_q(&mut self) {
+        self.chroma = [0.0; SEMITONES];
+        for (i, band) in self.cq_bands.iter().enumerate() {
+            let bins = &self.magnitude[band.start..band.start + band.weights.len()];
+            let energy: f32 = bins.iter().zip(&band.weights).map(|(m, w)| m * w).sum();
+            let (octave, n) = (i / SEMITONES, i % SEMITONES);
+            let energy = energy * self.octave_weights[octave];
+            self.octave_chroma[octave][n] = energy;
+            self.chroma[n] += energy;
+        }
+    }
+
+    /// Fold the detuning of this emission's fundamental peaks into the
+    /// running tuning estimate.
+    fn update_tuning(&mut self, bin_width: f32) {
+        let max_bin = self.magnitude.len() - 1;
+        let mut weighted = 0.0;
+        let mut total = 0.0;
+
+        // Fundamentals only: harmonic 1 of each (note, octave)
+        for (slot, &bin) in self.peak_bins.iter().enumerate().step_by(self.num_harmonics) {
+            if bin == 0 || bin == max_bin {
+                continue;
+            }
+            let (a, b, c) = (self.magnitude[bin - 1], self.magnitude[bin], self.magnitude[bin + 1]);
+            if b <= a || b < c {
+                continue; // edge of the search window, not a real peak
+            }
+
+            // Parabolic interpolation on log magnitude
+            let (la, lb, lc) = ((a + 1e-9).ln(), (b + 1e-9).ln(), (c + 1e-9).ln());
+            let denom = la - 2.0 * lb + lc;
+            let offset = if denom < 0.0 { 0.5 * (la - lc) / denom } else { 0.0 };
+
+            let n = slot / (self.num_octaves * self.num_harmonics);
+            let octave = (slot / self.num_harmonics) % self.num_octaves + 1;
+            let ideal = self.note_frequencies[n] * octave as f32;
+            let cents = 1200.0 * ((bin as f32 + offset) * bin_width / ideal).log2();
+
+            let weight = b * b;
+            weighted += cents * weight;
+            total += weight;
+        }
+
+        if total <= f32::EPSILON {
+            return;
+        }
+        let estimate = weighted / total;
+        if self.tuning_ready {
+            self.tuning_cents += TUNING_ADAPTATION * (estimate - self.tuning_cents);
+        } else {
+            self.tuning_cents = estimate;
+            self.tuning_ready = true;
+        }
+    }
+
+    /// Scale the fresh chroma to the configured norm.
+    #[inline]
+    fn apply_normalization(&mut self) {
+        let norm = match self.normalize {
+            NormKind::None => return,
+            NormKind::L1 => self.chroma.iter().map(|c| c.abs()).sum(),
+            NormKind::L2 => self.chroma.iter().map(|c| c * c).sum::<f32>().sqrt(),
+            NormKind::Max => self.chroma.iter().fold(0.0_f32, |m, c| m.max(c.abs())),
+        };
+        if norm > 0.0 {
+            for c in &mut self.chroma {
+                *c /= norm;
+            }
+        }
+    }
+
+    /// Blend the fresh chroma into the moving average of previous emissions.
+    #[inline]
+    fn apply_smoothing(&mut self) {
+        let alpha = self.smoothing_alpha;
+        if let Some(prev) = &self.smoothed {
+            for (c, &p) in self.chroma.iter_mut().zip(prev) {
+                *c = alpha * *c + (1.0 - alpha) * p;
+            }
+        }
+        self.smoothed = Some(self.chroma);
     }
+
+    /// Blend the fresh chroma with the decayed envelope of previous emissions.
+    #[inline]
+    fn apply_carryover(&mut self) {
+        for (held, c) in self.carryover.iter_mut().zip(self.chroma.iter_mut()) {
+            *held = c.max(*held * self.note_carryover);
+            *c = *held;
+        }
+    }
+}
+
+/// List the pitch classes whose energy reaches `threshold * max_bin`,
+/// sorted by energy (loudest first).
+///
+/// `threshold` is relative to the loudest bin: `0.5` keeps every note at least
+/// half as loud as the strongest one, `0.0` keeps every non-zero bin. Returns an
+/// empty `Vec` for an all-zero chromagram.
+pub fn active_notes(chroma: &[f32; SEMITONES], threshold: f32) -> Vec<NoteName> {
+    let max_bin = chroma.iter().cloned().fold(0.0_f32, f32::max);
+    if max_bin <= 0.0 {
+        return Vec::new();
+    }
+
+    let cutoff = threshold * max_bin;
+    let mut active: Vec<(usize, f32)> = chroma
+        .iter()
+        .enumerate()
+        .filter(|&(_, &e)| e > 0.0 && e >= cutoff)
+        .map(|(i, &e)| (i, e))
+        .collect();
+    active.sort_by(|a, b| b.1.total_cmp(&a.1));
+    active.into_iter().map(|(i, _)| NoteName::from_idx(i)).collect()
+}
+
+/// Rotate a chromagram by `semitones`, up for positive values and down for
+/// negative ones.
+///
+/// Bins wrap around the octave, so any offset works and offsets 12 apart
+/// give the same result: moving C major up 2 gives D major, and so does
+/// moving it down 10.
+pub fn transpose_chroma(chroma: &[f32; SEMITONES], semitones: i32) -> [f32; SEMITONES] {
+    let mut rotated = *chroma;
+    rotated.rotate_right(semitones.rem_euclid(SEMITONES as i32) as usize);
+    rotated
 }
 
-/// Approximate cosine for window generation.
-const fn cos_const(mut x: f32) -> f32 {
-    let two_pi = 2.0 * PI;
-    while x < -PI { x += two_pi; }
-    while x > PI  { x -= two_pi; }
-    let x2 = x * x;
-    let x4 = x2 * x2;
-    let x6 = x4 * x2;
-    let x8 = x4 * x4;
-    1.0 - x2 * 0.5 + x4 * (1.0 / 24.0)
-        - x6 * (1.0 / 720.0)
-        + x8 * (1.0 / 40320.0)
+/// Fold mel-band energies into a 12-bin chromagram.
+///
+/// `mel_freqs[i]` must be the center frequency in Hz of band `mel[i]` (for
+/// example as returned by librosa's `mel_frequencies`). Each band's energy is
+/// added to the pitch class nearest its center, using the same 440 Hz equal
+/// temperament reference as `Chromagram`. Bands centered outside the piano
+/// range (A0..C8, 27.5..4186 Hz), including the 0 Hz band, are ignored, and
+/// so are trailing entries when the slices differ in length.
+///
+/// Mel bands get wide at high frequencies, so upper bands smear across
+/// several semitones; the result is most meaningful with many bands.
+pub fn mel_to_chroma(mel: &[f32], mel_freqs: &[f32]) -> [f32; SEMITONES] {
+    let mut chroma = [0.0; SEMITONES];
+    for (&energy, &freq) in mel.iter().zip(mel_freqs) {
+        if !(MUSICAL_RANGE_HZ.0..=MUSICAL_RANGE_HZ.1).contains(&freq) {
+            continue;
+        }
+        chroma[pitch_class(freq)] += energy;
+    }
+    chroma
 }
 
-/// Compile‐time Hamming window.
-const fn make_hamming_window() -> [f32; BUFFER_SIZE] {
-    let mut w = [0.0; BUFFER_SIZE];
-    let mut n = 0;
-    while n < BUFFER_SIZE {
-        let phase = 2.0 * PI * n as f32 / (BUFFER_SIZE as f32 - 1.0);
-        w[n] = 0.54 - 0.46 * cos_const(phase);
-        n += 1;
+/// Combine chromagrams of separated bass and harmony stems into a single
+/// detection input.
+///
+/// Both stems are scaled so their loudest bin is 1.0, then the bass is added
+/// at half weight: `harmony / max(harmony) + 0.5 * bass / max(bass)`. The
+/// harmony stem (keys, guitars, pads) then decides the chord quality, while
+/// the bass adds energy to the note it plays, which settles the root when
+/// the harmony alone is ambiguous (C-E-G over an A bass reads as Am7). Half
+/// weight keeps passing tones in a walking bass from overriding the
+/// harmony; raise the share of the bass by mixing the normalized stems
+/// yourself if your bass parts stick to roots. A silent stem contributes
+/// nothing.
+pub fn fuse_stem_chroma(
+    bass: &[f32; SEMITONES],
+    harmony: &[f32; SEMITONES],
+) -> [f32; SEMITONES] {
+    let scale = |chroma: &[f32; SEMITONES]| {
+        let peak = chroma.iter().cloned().fold(0.0_f32, f32::max);
+        if peak > 0.0 { 1.0 / peak } else { 0.0 }
+    };
+    let (bass_scale, harmony_scale) = (BASS_STEM_WEIGHT * scale(bass), scale(harmony));
+
+    let mut fused = [0.0; SEMITONES];
+    for (i, f) in fused.iter_mut().enumerate() {
+        *f = harmony[i] * harmony_scale + bass[i] * bass_scale;
     }
-    w
+    fused
+}
+
+/// Nearest pitch class (0 = C) of a frequency in Hz.
+#[inline]
+fn pitch_class(freq: f32) -> usize {
+    let semitones = (12.0 * (freq / C3_HZ).log2()).round() as i32;
+    semitones.rem_euclid(SEMITONES as i32) as usize
+}
+
+/// Build one constant-Q band per semitone over `num_octaves` octaves from
+/// `note_frequencies`, octave-major.
+///
+/// Each band spans a quarter-tone either side of its center with a Hann
+/// shape in log frequency, so it falls to zero where the next one starts.
+/// A band too narrow to contain any bin falls back to its nearest bin;
+/// bands above `max_bin` are empty.
+fn constant_q_bands(
+    note_frequencies: &[f32; SEMITONES],
+    num_octaves: usize,
+    bin_width: f32,
+    max_bin: usize,
+) -> Vec<CqBand> {
+    let half_step = 2f32.powf(1.0 / 24.0);
+    let mut bands = Vec::with_capacity(num_octaves * SEMITONES);
+    for octave in 0..num_octaves {
+        for &base in note_frequencies {
+            let center = base * 2f32.powi(octave as i32);
+            let lo = ((center / half_step / bin_width).ceil() as usize).min(max_bin + 1);
+            let hi = ((center * half_step / bin_width).floor() as usize).min(max_bin);
+
+            let mut weights: Vec<f32> = (lo..=hi)
+                .map(|k| {
+                    let semitones = 12.0 * (k as f32 * bin_width / center).log2();
+                    0.5 * (1.0 + (2.0 * PI * semitones).cos())
+                })
+                .collect();
+            let mut start = lo;
+            if weights.iter().all(|&w| w <= 0.0) {
+                start = (center / bin_width).round() as usize;
+                weights = if start <= max_bin { vec![1.0] } else { Vec::new() };
+                start = start.min(max_bin);
+            }
+            bands.push(CqBand { start, weights });
+        }
+    }
+    bands
+}
+
+/// Convert an `f32` constant to the sample type
+#[inline]
+fn lift<T: Sample>(value: f32) -> T {
+    T::from_f32(value).expect("f32 and f64 represent every f32")
+}
+
+/// A-weighting magnitude response at `freq` Hz, normalized to 1.0 at 1 kHz.
+fn a_weighting(freq: f32) -> f32 {
+    let response = |f2: f32| {
+        (12194.0f32.powi(2) * f2 * f2)
+            / ((f2 + 20.6f32.powi(2))
+                * ((f2 + 107.7f32.powi(2)) * (f2 + 737.9f32.powi(2))).sqrt()
+                * (f2 + 12194.0f32.powi(2)))
+    };
+    response(freq * freq) / response(1000.0 * 1000.0)
 }
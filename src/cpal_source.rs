@@ -0,0 +1,191 @@
+//! Cpal source
+//!
+//! Live chord detection from a `cpal` input device.
+
+use std::sync::mpsc::{self, Receiver};
+
+use cpal::traits::{DeviceTrait, StreamTrait};
+use cpal::{FromSample, SampleFormat, SizedSample, Stream, StreamError};
+use thiserror::Error;
+
+use crate::chord_detector::{Chord, ChordDetector, ChordDetectorBuilder, ChordError};
+use crate::chromagram::{Chromagram, ChromagramBuilder, ChromagramError};
+
+/// Errors returned while setting up a [`CpalChordSource`].
+#[derive(Debug, Error)]
+pub enum CpalSourceError {
+    /// The device could not report its default input configuration.
+    #[error(transparent)]
+    DefaultConfig(#[from] cpal::DefaultStreamConfigError),
+
+    /// The input stream could not be built.
+    #[error(transparent)]
+    BuildStream(#[from] cpal::BuildStreamError),
+
+    /// The input stream could not be started.
+    #[error(transparent)]
+    PlayStream(#[from] cpal::PlayStreamError),
+
+    /// The device delivers a sample format this helper does not convert.
+    #[error("unsupported sample format {0}")]
+    UnsupportedFormat(SampleFormat),
+
+    /// The chromagram configuration was invalid.
+    #[error(transparent)]
+    Chromagram(#[from] ChromagramError),
+
+    /// The chord detector configuration was invalid.
+    #[error(transparent)]
+    Chord(#[from] ChordError),
+}
+
+/// A running input stream that detects chords in the background.
+///
+/// The device is captured at its default input configuration. Every
+/// callback downmixes the interleaved channels to mono, buffers them into
+/// frames of the chromagram's `frame_size` and runs the detector on each
+/// emitted chromagram; chords (and stream errors) arrive on a channel that
+/// the caller polls with [`CpalChordSource::recv`] or
+/// [`CpalChordSource::try_recv`]. Capture stops when the source is dropped.
+pub struct CpalChordSource {
+    // Kept alive for as long as chords should flow
+    _stream: Stream,
+    receiver: Receiver<Result<Chord, StreamError>>,
+    sample_rate: u32,
+}
+
+impl CpalChordSource {
+    /// Start capturing from `device` and detecting chords.
+    ///
+    /// The chromagram is built at the device's native sample rate: any
+    /// `sampling_rate` set on `chroma_builder` is replaced, so no resampling
+    /// is needed. All other settings of both builders are kept. Signed and
+    /// unsigned 8/16/32-bit integer and 32/64-bit float devices are
+    /// supported.
+    pub fn build(
+        device: &cpal::Device,
+        chroma_builder: ChromagramBuilder,
+        detector_builder: ChordDetectorBuilder,
+    ) -> Result<Self, CpalSourceError> {
+        let supported = device.default_input_config()?;
+        let sample_rate = supported.sample_rate().0;
+        let chromagram = chroma_builder.sampling_rate(sample_rate as usize).build()?;
+        let detector = detector_builder.try_build()?;
+
+        let config = supported.config();
+        let channels = config.channels as usize;
+        let (sender, receiver) = mpsc::channel();
+        let pipeline = Pipeline::new(chromagram, detector, channels, sender.clone());
+        let on_error = move |e| {
+            let _ = sender.send(Err(e));
+        };
+
+        let stream = match supported.sample_format() {
+            SampleFormat::I8 => build_stream::<i8>(device, &config, pipeline, on_error),
+            SampleFormat::I16 => build_stream::<i16>(device, &config, pipeline, on_error),
+            SampleFormat::I32 => build_stream::<i32>(device, &config, pipeline, on_error),
+            SampleFormat::U8 => build_stream::<u8>(device, &config, pipeline, on_error),
+            SampleFormat::U16 => build_stream::<u16>(device, &config, pipeline, on_error),
+            SampleFormat::U32 => build_stream::<u32>(device, &config, pipeline, on_error),
+            SampleFormat::F32 => build_stream::<f32>(device, &config, pipeline, on_error),
+            SampleFormat::F64 => build_stream::<f64>(device, &config, pipeline, on_error),
+            format => return Err(CpalSourceError::UnsupportedFormat(format)),
+        }?;
+        stream.play()?;
+
+        Ok(CpalChordSource {
+            _stream: stream,
+            receiver,
+            sample_rate,
+        })
+    }
+
+    /// Sample rate the device is captured at, in Hz.
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Block until the next chord or stream error arrives.
+    ///
+    /// Returns `None` once the stream has shut down.
+    pub fn recv(&self) -> Option<Result<Chord, StreamError>> {
+        self.receiver.recv().ok()
+    }
+
+    /// Return the next chord or stream error if one is waiting, without
+    /// blocking.
+    pub fn try_recv(&self) -> Option<Result<Chord, StreamError>> {
+        self.receiver.try_recv().ok()
+    }
+}
+
+/// Audio-thread state: downmix, frame buffering and detection
+struct Pipeline {
+    chromagram: Chromagram,
+    detector: ChordDetector,
+    channels: usize,
+    frame: Vec<f32>,
+    sender: mpsc::Sender<Result<Chord, StreamError>>,
+}
+
+impl Pipeline {
+    fn new(
+        chromagram: Chromagram,
+        detector: ChordDetector,
+        channels: usize,
+        sender: mpsc::Sender<Result<Chord, StreamError>>,
+    ) -> Self {
+        let frame = Vec::with_capacity(chromagram.frame_size());
+        Pipeline {
+            chromagram,
+            detector,
+            channels: channels.max(1),
+            frame,
+            sender,
+        }
+    }
+
+    /// Downmix one interleaved callback buffer and detect on every
+    /// completed frame
+    fn process<T>(&mut self, data: &[T])
+    where
+        T: SizedSample,
+        f32: FromSample<T>,
+    {
+        let frame_size = self.chromagram.frame_size();
+        for group in data.chunks_exact(self.channels) {
+            let sum: f32 = group.iter().map(|s| s.to_sample::<f32>()).sum();
+            self.frame.push(sum / self.channels as f32);
+            if self.frame.len() < frame_size {
+                continue;
+            }
+
+            let chroma = self.chromagram.next_unchecked(&self.frame);
+            self.frame.clear();
+            if let Some(chroma) = chroma {
+                if let Ok(chord) = self.detector.detect_chord(&chroma) {
+                    let _ = self.sender.send(Ok(chord));
+                }
+            }
+        }
+    }
+}
+
+/// Build an input stream delivering samples of type `T` to `pipeline`
+fn build_stream<T>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    mut pipeline: Pipeline,
+    on_error: impl FnMut(StreamError) + Send + 'static,
+) -> Result<Stream, cpal::BuildStreamError>
+where
+    T: SizedSample,
+    f32: FromSample<T>,
+{
+    device.build_input_stream(
+        config,
+        move |data: &[T], _: &cpal::InputCallbackInfo| pipeline.process(data),
+        on_error,
+        None,
+    )
+}
@@ -0,0 +1,206 @@
+//! Key
+//!
+//! Estimate the musical key of a passage from accumulated chromagrams using
+//! the Krumhansl-Schmuckler key profiles.
+
+use crate::chord_detector::NoteName;
+use crate::math::sqrtf;
+
+const SEMITONES: usize = 12;
+
+/// Krumhansl-Kessler probe-tone ratings for a major key, tonic first
+const MAJOR_PROFILE: [f32; SEMITONES] = [
+    6.35, 2.23, 3.48, 2.33, 4.38, 4.09, 2.52, 5.19, 2.39, 3.66, 2.29, 2.88,
+];
+
+/// Krumhansl-Kessler probe-tone ratings for a minor key, tonic first
+const MINOR_PROFILE: [f32; SEMITONES] = [
+    6.33, 2.68, 3.52, 5.38, 2.60, 3.53, 2.54, 4.75, 3.98, 2.69, 3.34, 3.17,
+];
+
+/// Semitones above C of the natural notes C, D, E, F, G, A, B
+const LETTER_SEMITONES: [usize; 7] = [0, 2, 4, 5, 7, 9, 11];
+
+/// Letters (indices into `LETTER_SEMITONES`) in the order sharps enter a key
+/// signature: F C G D A E B. Flats enter in the reverse order.
+const SHARP_ORDER: [usize; 7] = [3, 0, 4, 1, 5, 2, 6];
+
+/// Flat, natural and sharp spelling of each letter
+const SPELLINGS: [[&str; 3]; 7] = [
+    ["Cb", "C", "C#"],
+    ["Db", "D", "D#"],
+    ["Eb", "E", "E#"],
+    ["Fb", "F", "F#"],
+    ["Gb", "G", "G#"],
+    ["Ab", "A", "A#"],
+    ["Bb", "B", "B#"],
+];
+
+/// Major or minor tonality.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum KeyMode {
+    /// Major key
+    Major,
+    /// Minor key
+    Minor,
+}
+
+/// Accumulates chromagrams and estimates the key they were played in.
+#[derive(Debug, Clone, Default)]
+pub struct KeyEstimator {
+    accumulated: [f32; SEMITONES],
+}
+
+impl KeyEstimator {
+    /// Create an estimator with no accumulated chroma.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add one chromagram to the running total.
+    pub fn add_frame(&mut self, chroma: &[f32; SEMITONES]) {
+        for (acc, &c) in self.accumulated.iter_mut().zip(chroma) {
+            *acc += c;
+        }
+    }
+
+    /// Forget all accumulated chroma.
+    pub fn reset(&mut self) {
+        self.accumulated = [0.0; SEMITONES];
+    }
+
+    /// The most likely tonic and mode, or `None` if no energy has been
+    /// accumulated.
+    ///
+    /// Correlates the accumulated chroma with the major and minor profiles
+    /// rotated to all 12 tonics and returns the best match.
+    pub fn estimate(&self) -> Option<(NoteName, KeyMode)> {
+        best_key(&self.accumulated)
+    }
+
+    /// Estimate the key of the whole passage, then reset for the next one.
+    ///
+    /// Same as `estimate` followed by `reset`.
+    pub fn finalize(&mut self) -> Option<(NoteName, KeyMode)> {
+        let key = self.estimate();
+        self.reset();
+        key
+    }
+
+    /// Estimate the key of a single chromagram, ignoring (and leaving
+    /// untouched) the accumulated total.
+    ///
+    /// Returns `None` for a chromagram with no contrast between bins, such
+    /// as silence.
+    pub fn estimate_key(&self, chroma: &[f32; SEMITONES]) -> Option<(NoteName, KeyMode)> {
+        best_key(chroma)
+    }
+
+    /// Key signature of the estimated key: the number of sharps (positive)
+    /// or flats (negative), and the mode.
+    ///
+    /// Keys six fifths away from C are spelled with sharps (F# major, D#
+    /// minor). Returns `(0, KeyMode::Major)` if nothing has been accumulated.
+    pub fn key_signature(&self) -> (i8, KeyMode) {
+        match self.estimate() {
+            Some((tonic, mode)) => (signature_of(tonic, mode), mode),
+            None => (0, KeyMode::Major),
+        }
+    }
+}
+
+/// Spell `note` the way it is written in the key of `key` `mode`.
+///
+/// Notes of the key take the letter and accidental of its signature, found
+/// on the circle of fifths: pitch class 10 reads `Bb` in F major but `A#` in
+/// B major, and pitch class 5 reads `E#` in F# major. Other notes are
+/// spelled with flats in flat keys and with sharps otherwise (including C
+/// major and A minor), so D#/Eb reads `Eb` in F major and `D#` in G major.
+/// Keys six fifths from C are treated as sharp keys (F# major, D# minor).
+/// An `Unknown` note spells as `?`, and an `Unknown` key falls back to the
+/// sharp spelling of `NoteName`'s `Display`.
+pub fn spell(note: NoteName, key: NoteName, mode: KeyMode) -> &'static str {
+    let (Some(pitch), Some(_)) = (note.to_semitone(), key.to_semitone()) else {
+        return note.symbol();
+    };
+    let signature = signature_of(key, mode);
+
+    // Accidental the signature puts on each letter
+    let mut in_key = [0i8; 7];
+    for i in 0..usize::from(signature.unsigned_abs()) {
+        if signature > 0 {
+            in_key[SHARP_ORDER[i]] = 1;
+        } else {
+            in_key[SHARP_ORDER[6 - i]] = -1;
+        }
+    }
+    for (letter, &accidental) in in_key.iter().enumerate() {
+        let sounding = (LETTER_SEMITONES[letter] as i8 + accidental).rem_euclid(SEMITONES as i8);
+        if sounding as usize == pitch {
+            return SPELLINGS[letter][(accidental + 1) as usize];
+        }
+    }
+
+    // Chromatic note: natural letter a semitone above, flattened, in flat keys
+    if signature < 0 {
+        let above = (pitch + 1) % SEMITONES;
+        if let Some(letter) = LETTER_SEMITONES.iter().position(|&s| s == above) {
+            return SPELLINGS[letter][0];
+        }
+    }
+    note.symbol()
+}
+
+/// Tonic and mode whose rotated profile correlates best with `chroma`
+fn best_key(chroma: &[f32; SEMITONES]) -> Option<(NoteName, KeyMode)> {
+    let mut best: Option<(f32, NoteName, KeyMode)> = None;
+    for tonic in 0..SEMITONES {
+        for (mode, profile) in [
+            (KeyMode::Major, &MAJOR_PROFILE),
+            (KeyMode::Minor, &MINOR_PROFILE),
+        ] {
+            let Some(r) = correlation(chroma, profile, tonic) else {
+                continue;
+            };
+            if best.is_none_or(|(best_r, _, _)| r > best_r) {
+                best = Some((r, NoteName::from_idx(tonic), mode));
+            }
+        }
+    }
+    best.map(|(_, tonic, mode)| (tonic, mode))
+}
+
+/// Sharps (positive) or flats (negative) in the signature of `tonic` `mode`
+fn signature_of(tonic: NoteName, mode: KeyMode) -> i8 {
+    let semitone = tonic as usize % SEMITONES;
+    let major = match mode {
+        KeyMode::Major => semitone,
+        KeyMode::Minor => (semitone + 3) % SEMITONES,
+    };
+    // Position on the circle of fifths, clockwise from C
+    let fifths = (major * 7 % SEMITONES) as i8;
+    if fifths > 6 {
+        fifths - SEMITONES as i8
+    } else {
+        fifths
+    }
+}
+
+/// Pearson correlation between `chroma` and `profile` rotated to `tonic`
+fn correlation(chroma: &[f32; SEMITONES], profile: &[f32; SEMITONES], tonic: usize) -> Option<f32> {
+    let mean_c = chroma.iter().sum::<f32>() / SEMITONES as f32;
+    let mean_p = profile.iter().sum::<f32>() / SEMITONES as f32;
+
+    let (mut cov, mut var_c, mut var_p) = (0.0, 0.0, 0.0);
+    for (i, &c) in chroma.iter().enumerate() {
+        let dc = c - mean_c;
+        let dp = profile[(i + SEMITONES - tonic) % SEMITONES] - mean_p;
+        cov += dc * dp;
+        var_c += dc * dc;
+        var_p += dp * dp;
+    }
+    if var_c <= 0.0 {
+        return None;
+    }
+    Some(cov / sqrtf(var_c * var_p))
+}
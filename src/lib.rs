@@ -36,23 +36,124 @@
 //! ```
 //!
 //! ## Features
-//! - `chromagram` (default): enables FFT‐based chromagram via `rustfft`
+//! - `std` (default): links the standard library; implies `alloc`
+//! - `alloc`: the minimum for a `no_std` build, see below
+//! - `chromagram` (default): enables FFT‐based chromagram via `realfft`,
+//!   along with the whole-clip `analysis` helpers and `ChordStream`;
+//!   requires `std`
+//! - `sliding-dft`: enables `ChromagramBuilder::incremental_spectrum`, a
+//!   sliding-DFT alternative to the per-emission FFT
+//! - `serde`: derives `Serialize`/`Deserialize` for `Chord`, `ChordKind` and
+//!   `NoteName`, and enables `ChromaRecording` for saving and replaying
+//!   chromagram streams as JSON; requires `std`
+//! - `cpal`: enables `CpalChordSource`, live chord detection from a `cpal`
+//!   input device; implies `chromagram`
+//! - `hound`: enables `analyze_wav`, chord detection over WAV files;
+//!   implies `chromagram`
+//! - `extended_chords`: adds the sixth, add9 and dominant ninth qualities
+//!   (`ChordKind::Major6`, `Minor6`, `Add9`, `DominantNinth`) to the
+//!   built-in set scored by every detector
+//!
+//! ## `no_std`
+//! Built with `default-features = false, features = ["alloc"]` the crate is
+//! `#![no_std]` and only needs a global allocator. Chord detection
+//! (`ChordDetector`, `Chord`, `NoteName`, `detect_chord_stateless`), key
+//! estimation (`KeyEstimator`) and timeline scoring (`compare_timeline`) are
+//! available; feed them chromagrams computed elsewhere, e.g. by an MCU's FFT
+//! peripheral. Float math falls back to `libm`.
 
+#![cfg_attr(not(feature = "std"), no_std)]
 #![deny(missing_docs)]
 #![deny(rustdoc::broken_intra_doc_links)]
 #![deny(rust_2018_idioms)]
 #![deny(clippy::all)]
 
+#[cfg(not(feature = "alloc"))]
+compile_error!("chord_detector needs the `alloc` feature (or `std`, which implies it)");
+
+extern crate alloc;
+
 /// High‐level chord detector API.
 pub use chord_detector::{
-    Chord, ChordDetector, ChordDetectorBuilder, ChordError, ChordKind, NoteName,
+    detect_chord_stateless, Chord, ChordDetector, ChordDetectorBuilder, ChordError, ChordKind,
+    ChordTemplate, DetectorConfig, NoteName, TemplateModel, ToneFit,
 };
 
 /// Streaming chromagram extractor.
-pub use chromagram::{Chromagram, ChromagramBuilder, ChromagramError};
+#[cfg(feature = "chromagram")]
+pub use chromagram::{
+    Chromagram, ChromagramBuilder, ChromagramError, DecimationQuality, NormKind, Sample,
+    TransformKind, WindowKind,
+};
+
+/// Whole-clip analysis helpers.
+#[cfg(feature = "chromagram")]
+pub use analysis::{detect_robust, detect_robust_top_n, AnalysisError};
+
+/// Iterator adapter from samples to chords.
+#[cfg(feature = "chromagram")]
+pub use stream::ChordStream;
+
+/// Timeline evaluation against a reference chart.
+pub use timeline::{compare_timeline, compare_timeline_with_tolerance, TimedChord, TimelineScore};
+
+/// Chord commit/hold hysteresis over a frame stream.
+pub use tracker::{ChordTracker, ChordTrackerBuilder};
+
+/// Chromagram stream recording and replay.
+#[cfg(feature = "serde")]
+pub use recording::{ChromaRecording, RecordedFrame};
+
+/// Key estimation from accumulated chroma.
+pub use key::{spell, KeyEstimator, KeyMode};
+
+/// Live chord detection from an audio input device.
+#[cfg(feature = "cpal")]
+pub use cpal_source::{CpalChordSource, CpalSourceError};
+
+/// WAV file analysis.
+#[cfg(feature = "hound")]
+pub use wav::{analyze_wav, WavError};
 
 /// Chromagram computation module.
+#[cfg(feature = "chromagram")]
 pub mod chromagram;
 
 /// Chord detection module.
 pub mod chord_detector;
+
+/// Sample-stream adapter module.
+#[cfg(feature = "chromagram")]
+pub mod stream;
+
+/// Timeline evaluation module.
+pub mod timeline;
+
+/// Chord hysteresis module.
+pub mod tracker;
+
+/// Whole-clip analysis module.
+#[cfg(feature = "chromagram")]
+pub mod analysis;
+
+/// Key estimation module.
+pub mod key;
+
+/// Incremental spectrum for the chromagram.
+#[cfg(feature = "sliding-dft")]
+mod sliding_dft;
+
+/// Float math that works with and without `std`.
+mod math;
+
+/// Chromagram recording module.
+#[cfg(feature = "serde")]
+pub mod recording;
+
+/// Audio input device module.
+#[cfg(feature = "cpal")]
+pub mod cpal_source;
+
+/// WAV file analysis module.
+#[cfg(feature = "hound")]
+pub mod wav;
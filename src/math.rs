@@ -0,0 +1,13 @@
+//! Math
+//!
+//! Float functions missing from `core`, forwarded to `std` when it is
+//! linked and to `libm` otherwise.
+
+/// Square root of `x`
+#[inline]
+pub(crate) fn sqrtf(x: f32) -> f32 {
+    #[cfg(feature = "std")]
+    return x.sqrt();
+    #[cfg(not(feature = "std"))]
+    return libm::sqrtf(x);
+}
@@ -0,0 +1,91 @@
+//! Recording
+//!
+//! Capture a stream of emitted chromagrams so that a misdetection can be
+//! saved as JSON and replayed later without the original audio.
+
+use serde::{Deserialize, Serialize};
+
+use crate::chord_detector::ChordDetector;
+use crate::timeline::TimedChord;
+
+const SEMITONES: usize = 12;
+
+/// A single chromagram frame and the time it was emitted, in seconds.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedFrame {
+    /// Emission time in seconds.
+    pub time: f64,
+    /// The 12-bin chromagram.
+    pub chroma: [f32; SEMITONES],
+}
+
+/// An ordered sequence of timestamped chromagram frames.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChromaRecording {
+    frames: Vec<RecordedFrame>,
+}
+
+impl ChromaRecording {
+    /// Create an empty recording.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a frame emitted at `time` seconds.
+    pub fn push(&mut self, time: f64, chroma: [f32; SEMITONES]) {
+        self.frames.push(RecordedFrame { time, chroma });
+    }
+
+    /// The recorded frames, in the order they were pushed.
+    pub fn frames(&self) -> &[RecordedFrame] {
+        &self.frames
+    }
+
+    /// Number of recorded frames.
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Whether no frames have been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Serialize the recording to a JSON string.
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string(self)
+    }
+
+    /// Parse a recording previously produced by [`ChromaRecording::to_json`].
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    /// Reset `detector` and run every frame through it in order.
+    ///
+    /// Each chord spans from its frame's time to the next frame's time; the
+    /// last chord reuses the preceding frame interval (or has zero length if
+    /// it is the only frame). Because the detector is reset first, replaying
+    /// the same recording with the same configuration always yields the same
+    /// timeline.
+    pub fn replay(&self, detector: &mut ChordDetector) -> Vec<TimedChord> {
+        detector.reset();
+
+        let mut timeline = Vec::with_capacity(self.frames.len());
+        for (i, frame) in self.frames.iter().enumerate() {
+            let end = match self.frames.get(i + 1) {
+                Some(next) => next.time,
+                None if i > 0 => frame.time + (frame.time - self.frames[i - 1].time),
+                None => frame.time,
+            };
+            if let Ok(chord) = detector.detect_chord(&frame.chroma) {
+                timeline.push(TimedChord {
+                    chord,
+                    start: frame.time,
+                    end,
+                });
+            }
+        }
+        timeline
+    }
+}
@@ -0,0 +1,118 @@
+//! Sliding DFT
+//!
+//! Incremental spectrum for the chromagram: instead of transforming the whole
+//! analysis buffer on every emission, each tracked DFT bin is updated as
+//! samples enter and leave the window. Only the bins the chromagram actually
+//! reads are tracked, and the analysis window is applied in the frequency
+//! domain as a short convolution of neighbouring bins.
+
+use std::f64::consts::PI;
+
+use realfft::num_complex::Complex;
+
+/// Incrementally updated DFT over a subset of bins.
+#[derive(Clone)]
+pub(crate) struct SlidingDft {
+    len: usize,
+    /// Index into `state` for every bin `0..=len/2` (`usize::MAX` if untracked)
+    slot: Vec<usize>,
+    /// Running DFT of each tracked raw bin, in ascending bin order, split
+    /// into real and imaginary parts so the update loop vectorizes
+    re: Vec<f64>,
+    im: Vec<f64>,
+    /// `e^{2πik/len}` for each tracked bin, split the same way
+    twiddle_re: Vec<f64>,
+    twiddle_im: Vec<f64>,
+    /// Bins whose windowed magnitude is produced
+    out_bins: Vec<usize>,
+    /// Window cosine-series coefficients `a0 - a1 cos + a2 cos2 - ...`
+    window_terms: &'static [f32],
+}
+
+impl SlidingDft {
+    /// Track the windowed magnitudes of `out_bins` for a DFT of length `len`.
+    pub(crate) fn new(len: usize, out_bins: Vec<usize>, window_terms: &'static [f32]) -> Self {
+        let half = len / 2;
+        let mut needed = vec![false; half + 1];
+        for &k in &out_bins {
+            for m in 0..window_terms.len() {
+                needed[reflect(k as isize - m as isize, len)] = true;
+                needed[reflect((k + m) as isize, len)] = true;
+            }
+        }
+
+        let raw_bins: Vec<usize> = (0..=half).filter(|&k| needed[k]).collect();
+        let mut slot = vec![usize::MAX; half + 1];
+        for (i, &k) in raw_bins.iter().enumerate() {
+            slot[k] = i;
+        }
+        let angle = |k: usize| 2.0 * PI * k as f64 / len as f64;
+
+        SlidingDft {
+            len,
+            re: vec![0.0; raw_bins.len()],
+            im: vec![0.0; raw_bins.len()],
+            twiddle_re: raw_bins.iter().map(|&k| angle(k).cos()).collect(),
+            twiddle_im: raw_bins.iter().map(|&k| angle(k).sin()).collect(),
+            slot,
+            out_bins,
+            window_terms,
+        }
+    }
+
+    /// Slide the window by one sample: `incoming` enters, `outgoing` (the
+    /// oldest sample) leaves.
+    #[inline]
+    pub(crate) fn push(&mut self, incoming: f64, outgoing: f64) {
+        let delta = incoming - outgoing;
+        let twiddles = self.twiddle_re.iter().zip(&self.twiddle_im);
+        for ((re, im), (&c, &s)) in self.re.iter_mut().zip(&mut self.im).zip(twiddles) {
+            let r = *re + delta;
+            *re = r * c - *im * s;
+            *im = r * s + *im * c;
+        }
+    }
+
+    /// Write the windowed magnitude of every tracked bin into `magnitude`.
+    ///
+    /// Untracked bins are left untouched.
+    pub(crate) fn magnitudes(&self, magnitude: &mut [f32]) {
+        for &k in &self.out_bins {
+            let mut acc = self.raw(k as isize) * self.window_terms[0] as f64;
+            for (m, &a) in self.window_terms.iter().enumerate().skip(1) {
+                let sign = if m % 2 == 0 { 0.5 } else { -0.5 };
+                let pair = self.raw(k as isize - m as isize) + self.raw((k + m) as isize);
+                acc += pair * (sign * a as f64);
+            }
+            magnitude[k] = acc.norm() as f32;
+        }
+    }
+
+    /// Zero every running sum, as if the window held only silence.
+    pub(crate) fn reset(&mut self) {
+        self.re.fill(0.0);
+        self.im.fill(0.0);
+    }
+
+    /// Raw DFT value at any integer bin, using conjugate symmetry of real
+    /// input for bins outside `0..=len/2`.
+    #[inline]
+    fn raw(&self, k: isize) -> Complex<f64> {
+        let len = self.len as isize;
+        let k = k.rem_euclid(len);
+        if k <= len / 2 {
+            let i = self.slot[k as usize];
+            Complex::new(self.re[i], self.im[i])
+        } else {
+            let i = self.slot[(len - k) as usize];
+            Complex::new(self.re[i], -self.im[i])
+        }
+    }
+}
+
+/// Map any integer bin to its mirror in `0..=len/2`
+fn reflect(k: isize, len: usize) -> usize {
+    let len = len as isize;
+    let k = k.rem_euclid(len);
+    (if k <= len / 2 { k } else { len - k }) as usize
+}
@@ -0,0 +1,75 @@
+//! Stream
+//!
+//! Iterator adapter that runs a sample stream through a chromagram and a
+//! chord detector.
+
+use crate::analysis::AnalysisError;
+use crate::chord_detector::{Chord, ChordDetector};
+use crate::chromagram::Chromagram;
+
+/// Iterator over the chords detected in a stream of mono samples.
+///
+/// Samples are gathered into frames of the chromagram's `frame_size` and
+/// pushed through [`Chromagram::next`]; a chord is yielded for every
+/// chromagram emitted, via [`ChordDetector::detect_chord`]. The stream ends
+/// when the samples do, and a trailing partial frame is dropped.
+pub struct ChordStream<I> {
+    samples: I,
+    chromagram: Chromagram,
+    detector: ChordDetector,
+    frame: Vec<f32>,
+    onsets_only: bool,
+}
+
+impl<I: Iterator<Item = f32>> ChordStream<I> {
+    /// Detect chords in `samples` with the given pipeline.
+    pub fn from_samples(samples: I, chromagram: Chromagram, detector: ChordDetector) -> Self {
+        let frame = Vec::with_capacity(chromagram.frame_size());
+        ChordStream {
+            samples,
+            chromagram,
+            detector,
+            frame,
+            onsets_only: false,
+        }
+    }
+
+    /// Only yield a chord for chromagrams that start an onset (default
+    /// false).
+    ///
+    /// Emissions without [`Chromagram::onset_detected`] are skipped, so a
+    /// sustained chord is reported once instead of on every hop.
+    pub fn onsets_only(mut self, enabled: bool) -> Self {
+        self.onsets_only = enabled;
+        self
+    }
+
+    /// Stop streaming and hand back the chromagram and detector.
+    pub fn into_parts(self) -> (Chromagram, ChordDetector) {
+        (self.chromagram, self.detector)
+    }
+}
+
+impl<I: Iterator<Item = f32>> Iterator for ChordStream<I> {
+    type Item = Result<Chord, AnalysisError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let frame_size = self.chromagram.frame_size();
+        loop {
+            self.frame.clear();
+            self.frame.extend(self.samples.by_ref().take(frame_size));
+            if self.frame.len() < frame_size {
+                return None;
+            }
+
+            match self.chromagram.next(&self.frame) {
+                Ok(Some(_)) if self.onsets_only && !self.chromagram.onset_detected() => continue,
+                Ok(Some(chroma)) => {
+                    return Some(self.detector.detect_chord(&chroma).map_err(Into::into));
+                }
+                Ok(None) => continue,
+                Err(e) => return Some(Err(e.into())),
+            }
+        }
+    }
+}
@@ -0,0 +1,127 @@
+//! Timeline
+//!
+//! Evaluation utilities for grading a detected chord timeline against a
+//! reference chart.
+
+use alloc::vec::Vec;
+
+use crate::chord_detector::Chord;
+
+/// A chord held over a time span, in seconds.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TimedChord {
+    /// The chord sounding over the span.
+    pub chord: Chord,
+    /// Start of the span, in seconds.
+    pub start: f64,
+    /// End of the span, in seconds.
+    pub end: f64,
+}
+
+impl TimedChord {
+    /// Length of the span in seconds (zero if `end <= start`).
+    pub fn duration(&self) -> f64 {
+        (self.end - self.start).max(0.0)
+    }
+}
+
+/// Accuracy of a detected timeline measured against a reference.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimelineScore {
+    /// Fraction (0.0..=1.0) of each reference chord's span covered by a
+    /// matching detection, in reference order.
+    pub per_chord: Vec<f64>,
+    /// Fraction (0.0..=1.0) of the total reference time covered by a
+    /// matching detection.
+    pub overall: f64,
+}
+
+/// Compare a detected timeline against a reference with exact timing.
+///
+/// Equivalent to `compare_timeline_with_tolerance(detected, reference, 0.0)`.
+pub fn compare_timeline(detected: &[TimedChord], reference: &[TimedChord]) -> TimelineScore {
+    compare_timeline_with_tolerance(detected, reference, 0.0)
+}
+
+/// Compare a detected timeline against a reference, forgiving boundary errors
+/// of up to `tolerance` seconds.
+///
+/// Two chords match when their root and quality agree; confidence is ignored.
+/// Each matching detection is widened by `tolerance` on both sides before
+/// being intersected with the reference span, so a detection that starts or
+/// ends slightly late still covers the reference. Overlapping detections are
+/// merged, so no stretch of time is counted twice.
+///
+/// Zero-length reference chords score `0.0` and do not contribute to
+/// `overall`. An empty or zero-length reference yields `overall = 0.0`.
+pub fn compare_timeline_with_tolerance(
+    detected: &[TimedChord],
+    reference: &[TimedChord],
+    tolerance: f64,
+) -> TimelineScore {
+    let tolerance = tolerance.max(0.0);
+    let mut per_chord = Vec::with_capacity(reference.len());
+    let mut spans: Vec<(f64, f64)> = Vec::new();
+    let mut total = 0.0;
+    let mut matched_total = 0.0;
+
+    for r in reference {
+        let duration = r.duration();
+        if duration <= 0.0 {
+            per_chord.push(0.0);
+            continue;
+        }
+
+        // Matching detections clipped to the reference span
+        spans.clear();
+        for d in detected {
+            if !same_chord(&d.chord, &r.chord) {
+                continue;
+            }
+            let lo = (d.start - tolerance).max(r.start);
+            let hi = (d.end + tolerance).min(r.end);
+            if hi > lo {
+                spans.push((lo, hi));
+            }
+        }
+
+        let matched = merged_length(&mut spans);
+        per_chord.push(matched / duration);
+        total += duration;
+        matched_total += matched;
+    }
+
+    let overall = if total > 0.0 {
+        matched_total / total
+    } else {
+        0.0
+    };
+    TimelineScore { per_chord, overall }
+}
+
+#[inline]
+fn same_chord(a: &Chord, b: &Chord) -> bool {
+    a.root == b.root && a.quality == b.quality
+}
+
+/// Total length covered by the union of `spans`.
+fn merged_length(spans: &mut [(f64, f64)]) -> f64 {
+    spans.sort_unstable_by(|a, b| a.0.total_cmp(&b.0));
+
+    let mut length = 0.0;
+    let mut current: Option<(f64, f64)> = None;
+    for &(lo, hi) in spans.iter() {
+        current = match current {
+            Some((c_lo, c_hi)) if lo <= c_hi => Some((c_lo, c_hi.max(hi))),
+            Some((c_lo, c_hi)) => {
+                length += c_hi - c_lo;
+                Some((lo, hi))
+            }
+            None => Some((lo, hi)),
+        };
+    }
+    if let Some((c_lo, c_hi)) = current {
+        length += c_hi - c_lo;
+    }
+    length
+}
@@ -0,0 +1,220 @@
+//! Tracker
+//!
+//! Hysteresis over frame-by-frame detections: a chord is committed only once
+//! it has persisted, and held until another has persisted long enough to
+//! replace it.
+
+use alloc::string::ToString;
+
+use crate::chord_detector::{Chord, ChordDetector, ChordError, ChordKind, NoteName};
+
+/// Default number of consecutive frames a chord needs to be committed.
+const DEFAULT_COMMIT_FRAMES: usize = 3;
+
+/// Default number of consecutive frames without the committed chord before
+/// it is released.
+const DEFAULT_RELEASE_FRAMES: usize = 3;
+
+/// Builder for a [`ChordTracker`].
+pub struct ChordTrackerBuilder {
+    detector: Option<ChordDetector>,
+    commit_frames: usize,
+    release_frames: usize,
+}
+
+impl ChordTrackerBuilder {
+    /// Start with a default [`ChordDetector`], `commit_frames = 3` and
+    /// `release_frames = 3`.
+    pub fn new() -> Self {
+        ChordTrackerBuilder {
+            detector: None,
+            commit_frames: DEFAULT_COMMIT_FRAMES,
+            release_frames: DEFAULT_RELEASE_FRAMES,
+        }
+    }
+
+    /// Set the detector run on every frame (default `ChordDetector::new()`).
+    pub fn detector(mut self, detector: ChordDetector) -> Self {
+        self.detector = Some(detector);
+        self
+    }
+
+    /// Set how many consecutive frames a chord must be detected before it
+    /// is committed (at least 1, default 3).
+    pub fn commit_frames(mut self, frames: usize) -> Self {
+        self.commit_frames = frames;
+        self
+    }
+
+    /// Set how many consecutive frames the committed chord must be missing
+    /// before it is released (at least 1, default 3).
+    ///
+    /// Until then a different chord cannot be committed however long it
+    /// persists, so marginal frames never replace the held chord.
+    pub fn release_frames(mut self, frames: usize) -> Self {
+        self.release_frames = frames;
+        self
+    }
+
+    /// Finalize and create the tracker.
+    ///
+    /// # Panics
+    /// If `commit_frames` or `release_frames` is zero. Use
+    /// [`ChordTrackerBuilder::try_build`] to handle this as an error.
+    pub fn build(self) -> ChordTracker {
+        match self.try_build() {
+            Ok(tracker) => tracker,
+            Err(e) => panic!("invalid chord tracker configuration: {e}"),
+        }
+    }
+
+    /// Finalize and create the tracker, reporting an invalid configuration.
+    ///
+    /// # Errors
+    /// `ChordError::InvalidArgument` if `commit_frames` or `release_frames`
+    /// is zero.
+    pub fn try_build(self) -> Result<ChordTracker, ChordError> {
+        for (arg, frames) in [
+            ("commit_frames", self.commit_frames),
+            ("release_frames", self.release_frames),
+        ] {
+            if frames == 0 {
+                return Err(ChordError::InvalidArgument {
+                    arg,
+                    msg: "must be at least 1".to_string(),
+                });
+            }
+        }
+        Ok(ChordTracker {
+            detector: self.detector.unwrap_or_default(),
+            commit_frames: self.commit_frames,
+            release_frames: self.release_frames,
+            committed: None,
+            candidate: None,
+            candidate_frames: 0,
+            missing_frames: 0,
+        })
+    }
+}
+
+impl Default for ChordTrackerBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Commits chords from a stream of chromagrams with hysteresis.
+///
+/// Every chromagram passed to [`ChordTracker::update`] is run through the
+/// detector, and the detection drives a small state machine:
+///
+/// - **Candidate counting.** A detected chord other than the committed one
+///   becomes the candidate; each consecutive frame with the same root and
+///   quality adds to its count. A different chord restarts the count, and
+///   a frame without a chord (unknown root) clears the candidate.
+/// - **Commit.** Once the candidate has been seen for `commit_frames`
+///   frames and nothing is held, it is committed and `update` returns it.
+/// - **Hold.** While a chord is committed, frames detecting it reset the
+///   release count, so a brief lapse does not lose it, and `update` returns
+///   `None`.
+/// - **Release.** After `release_frames` consecutive frames without the
+///   committed chord it is released. A candidate that has already reached
+///   `commit_frames` is committed in the same frame; otherwise the tracker
+///   holds nothing until a candidate does.
+///
+/// A switch from one chord to another therefore needs the new chord for at
+/// least `max(commit_frames, release_frames)` frames in a row.
+pub struct ChordTracker {
+    detector: ChordDetector,
+    commit_frames: usize,
+    release_frames: usize,
+    committed: Option<Chord>,
+    candidate: Option<Chord>,
+    candidate_frames: usize,
+    missing_frames: usize,
+}
+
+impl ChordTracker {
+    /// Start customizing with a builder.
+    pub fn builder() -> ChordTrackerBuilder {
+        ChordTrackerBuilder::new()
+    }
+
+    /// Create a tracker around a default detector with default thresholds.
+    pub fn new() -> Self {
+        ChordTrackerBuilder::new().build()
+    }
+
+    /// Detect the chord in `chroma` and advance the state machine.
+    ///
+    /// Returns `Some(chord)` only on the frame a chord is committed, with
+    /// the detection of that frame; `None` while holding, counting or idle.
+    ///
+    /// # Errors
+    /// Any error of [`ChordDetector::detect_chord`]; the state is left
+    /// unchanged.
+    pub fn update(&mut self, chroma: &[f32]) -> Result<Option<Chord>, ChordError> {
+        let chord = self.detector.detect_chord(chroma)?;
+        let key = (chord.root, chord.quality);
+
+        if self.committed.is_some_and(|held| (held.root, held.quality) == key) {
+            self.missing_frames = 0;
+            self.candidate = None;
+            self.candidate_frames = 0;
+            return Ok(None);
+        }
+
+        if chord.root == NoteName::Unknown || chord.quality == ChordKind::Unknown {
+            self.candidate = None;
+            self.candidate_frames = 0;
+        } else if self.candidate.is_some_and(|c| (c.root, c.quality) == key) {
+            self.candidate_frames += 1;
+        } else {
+            self.candidate = Some(chord);
+            self.candidate_frames = 1;
+        }
+
+        if self.committed.is_some() {
+            self.missing_frames += 1;
+            if self.missing_frames < self.release_frames {
+                return Ok(None);
+            }
+            self.committed = None;
+            self.missing_frames = 0;
+        }
+
+        if self.candidate_frames >= self.commit_frames {
+            self.committed = Some(chord);
+            self.candidate = None;
+            self.candidate_frames = 0;
+            self.missing_frames = 0;
+            return Ok(Some(chord));
+        }
+        Ok(None)
+    }
+
+    /// The chord currently held, if any.
+    pub fn committed(&self) -> Option<Chord> {
+        self.committed
+    }
+
+    /// Forget the held chord and any candidate, and reset the detector.
+    pub fn reset(&mut self) {
+        self.detector.reset();
+        self.committed = None;
+        self.candidate = None;
+        self.candidate_frames = 0;
+        self.missing_frames = 0;
+    }
+
+    /// The wrapped detector.
+    pub fn detector(&self) -> &ChordDetector {
+        &self.detector
+    }
+}
+
+impl Default for ChordTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
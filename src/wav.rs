@@ -0,0 +1,78 @@
+//! Wav
+//!
+//! Offline chord detection over WAV files, read with `hound`.
+
+use std::path::Path;
+
+use hound::{SampleFormat, WavReader};
+use thiserror::Error;
+
+use crate::chord_detector::{Chord, ChordDetectorBuilder, ChordError};
+use crate::chromagram::{ChromagramBuilder, ChromagramError};
+
+/// Errors returned by [`analyze_wav`].
+#[derive(Debug, Error)]
+pub enum WavError {
+    /// The file could not be opened or decoded.
+    #[error(transparent)]
+    Read(#[from] hound::Error),
+
+    /// The chromagram pipeline failed.
+    #[error(transparent)]
+    Chromagram(#[from] ChromagramError),
+
+    /// Chord detection failed.
+    #[error(transparent)]
+    Chord(#[from] ChordError),
+}
+
+/// Detect the chord sequence of a WAV file, one chord per chromagram.
+///
+/// The file is downmixed to mono `f32` in [-1, 1] and streamed in frames of
+/// the chromagram's `frame_size`; a trailing partial frame is dropped. Any
+/// `sampling_rate` set on `chroma_builder` is replaced by the rate in the
+/// file header. Integer files of any bit depth up to 32 (8, 16, 24, ...)
+/// and 32-bit float files are supported, with any number of channels.
+pub fn analyze_wav<P: AsRef<Path>>(
+    path: P,
+    chroma_builder: ChromagramBuilder,
+    detector_builder: ChordDetectorBuilder,
+) -> Result<Vec<Chord>, WavError> {
+    let reader = WavReader::open(path)?;
+    let spec = reader.spec();
+    let samples = read_mono(reader)?;
+
+    let mut chromagram = chroma_builder
+        .sampling_rate(spec.sample_rate as usize)
+        .build()?;
+    let mut detector = detector_builder.try_build()?;
+
+    let mut chords = Vec::new();
+    for frame in samples.chunks_exact(chromagram.frame_size()) {
+        if let Some(chroma) = chromagram.next(frame)? {
+            chords.push(detector.detect_chord(&chroma)?);
+        }
+    }
+    Ok(chords)
+}
+
+/// Decode every sample, normalize to [-1, 1] and average the channels
+fn read_mono<R: std::io::Read>(reader: WavReader<R>) -> Result<Vec<f32>, hound::Error> {
+    let spec = reader.spec();
+    let interleaved: Vec<f32> = match spec.sample_format {
+        SampleFormat::Float => reader.into_samples::<f32>().collect::<Result<_, _>>()?,
+        SampleFormat::Int => {
+            let scale = 1.0 / (1u64 << (spec.bits_per_sample - 1)) as f32;
+            reader
+                .into_samples::<i32>()
+                .map(|s| s.map(|s| s as f32 * scale))
+                .collect::<Result<_, _>>()?
+        }
+    };
+
+    let channels = usize::from(spec.channels.max(1));
+    Ok(interleaved
+        .chunks_exact(channels)
+        .map(|group| group.iter().sum::<f32>() / channels as f32)
+        .collect())
+}
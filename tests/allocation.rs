@@ -0,0 +1,63 @@
+//! Checks that steady-state chromagram processing never allocates.
+#![cfg(feature = "chromagram")]
+
+use chord_detector::Chromagram;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::f32::consts::PI;
+
+/// System allocator that counts every allocation made on the current thread.
+struct CountingAlloc;
+
+thread_local! {
+    // Per thread, so the test harness allocating elsewhere is not counted
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+fn count_allocation() {
+    let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        count_allocation();
+        System.alloc(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        count_allocation();
+        System.realloc(ptr, layout, new_size)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+#[test]
+fn test_next_does_not_allocate() {
+    let mut chroma = Chromagram::builder().build().unwrap();
+    let samples: Vec<f32> = (0..44_100)
+        .map(|i| (2.0 * PI * 440.0 * i as f32 / 44_100.0).sin())
+        .collect();
+    let frames: Vec<&[f32]> = samples.chunks_exact(1024).collect();
+
+    // Warm up past the first emission
+    let (warmup, rest) = frames.split_at(frames.len() / 2);
+    assert!(warmup.iter().any(|f| chroma.next(f).unwrap().is_some()));
+
+    let before = ALLOCATIONS.with(Cell::get);
+    let mut emitted = 0;
+    for frame in rest {
+        if chroma.next(frame).unwrap().is_some() {
+            emitted += 1;
+        }
+    }
+    let after = ALLOCATIONS.with(Cell::get);
+
+    assert!(emitted > 0);
+    assert_eq!(after - before, 0, "next() allocated on the steady-state path");
+}
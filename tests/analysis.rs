@@ -0,0 +1,41 @@
+//! Tests for whole-clip analysis helpers.
+#![cfg(feature = "chromagram")]
+
+use chord_detector::{detect_robust, detect_robust_top_n, AnalysisError, ChordKind, NoteName};
+use std::f32::consts::PI;
+
+const SAMPLE_RATE: usize = 44_100;
+
+/// Generate `len` samples of equal-amplitude sines at each of `freqs`.
+fn tones(freqs: &[f32], len: usize) -> Vec<f32> {
+    (0..len)
+        .map(|i| {
+            let t = i as f32 / SAMPLE_RATE as f32;
+            freqs.iter().map(|f| (2.0 * PI * f * t).sin()).sum::<f32>() / freqs.len() as f32
+        })
+        .collect()
+}
+
+#[test]
+fn test_detect_robust_ignores_silence() {
+    // Silence, two seconds of C major, silence
+    let mut samples = vec![0.0; SAMPLE_RATE];
+    samples.extend(tones(&[261.63, 329.63, 392.0], 2 * SAMPLE_RATE));
+    samples.extend(vec![0.0; SAMPLE_RATE]);
+
+    let chord = detect_robust(&samples, SAMPLE_RATE).unwrap();
+    assert_eq!(chord.root, NoteName::C);
+    assert_eq!(chord.quality, ChordKind::Major);
+}
+
+#[test]
+fn test_detect_robust_errors() {
+    assert!(matches!(
+        detect_robust(&[0.0; 1024], SAMPLE_RATE),
+        Err(AnalysisError::NoChromagram)
+    ));
+    assert!(matches!(
+        detect_robust_top_n(&[0.0; 1024], SAMPLE_RATE, 0),
+        Err(AnalysisError::Chord(_))
+    ));
+}
@@ -1,4 +1,5 @@
 //! Integration tests for pitch, chord, and analyzer detection using real audio files.
+#![cfg(feature = "chromagram")]
 
 use chord_detector::{ChordDetectorBuilder, ChordKind, NoteName};
 use lazy_static::lazy_static;
@@ -42,21 +43,7 @@ struct TestFileInfo {
 }
 
 fn get_kind(str: &str) -> ChordKind {
-    match str {
-        "maj" => ChordKind::Major,
-        "min" => ChordKind::Minor,
-        "power" => ChordKind::PowerFifth,
-        "7" => ChordKind::DominantSeventh,
-        "maj7" => ChordKind::MajorSeventh,
-        "m7" => ChordKind::MinorSeventh,
-        "dim" => ChordKind::Diminished,
-        "aug" => ChordKind::Augmented,
-        "sus2" => ChordKind::SuspendedSecond,
-        "sus4" => ChordKind::SuspendedFourth,
-        other => {
-            panic!("unrecognized chord kind: `{other}`");
-        }
-    }
+    ChordKind::try_from(str).unwrap_or_else(|_| panic!("unrecognized chord kind: `{str}`"))
 }
 
 impl TestFileInfo {
@@ -70,23 +57,9 @@ impl TestFileInfo {
         // Parse root, e.g. "C#" or "Db"
         let note_name = &root_oct[..root_oct.len() - 1];
 
-        let root = match note_name {
-            "C" => NoteName::C,
-            "C#" => NoteName::Cs,
-            "D" => NoteName::D,
-            "D#" => NoteName::Ds,
-            "E" => NoteName::E,
-            "F" => NoteName::F,
-            "F#" => NoteName::Fs,
-            "G" => NoteName::G,
-            "G#" => NoteName::Gs,
-            "A" => NoteName::A,
-            "A#" => NoteName::As,
-            "B" => NoteName::B,
-            other => {
-                eprintln!("unrecognized note name: `{other}`");
-                return None;
-            }
+        let Ok(root) = note_name.parse::<NoteName>() else {
+            eprintln!("unrecognized note name: `{note_name}`");
+            return None;
         };
 
         Some(TestFileInfo {
@@ -128,6 +101,9 @@ struct Counters {
     augmented: usize,
     suspended_second: usize,
     suspended_fourth: usize,
+    half_diminished: usize,
+    diminished_seventh: usize,
+    unknown: usize,
 }
 
 impl Counters {
@@ -143,6 +119,12 @@ impl Counters {
             ChordKind::Augmented => self.augmented += 1,
             ChordKind::SuspendedSecond => self.suspended_second += 1,
             ChordKind::SuspendedFourth => self.suspended_fourth += 1,
+            ChordKind::HalfDiminished => self.half_diminished += 1,
+            ChordKind::DiminishedSeventh => self.diminished_seventh += 1,
+            ChordKind::Unknown | ChordKind::Custom(_) => self.unknown += 1,
+            // No sample file is named after an extended quality
+            #[cfg(feature = "extended_chords")]
+            _ => self.unknown += 1,
         }
     }
 }
@@ -215,7 +197,8 @@ fn test_chord_detector() {
 
         panic!(
             "{} chord tests failed:\n Major={}  Minor={}  Power={}  \
-             Dom7={}  Maj7={}  Min7={}  Dim={}  Aug={}  Sus2={}  Sus4={}\n\n{}",
+             Dom7={}  Maj7={}  Min7={}  Dim={}  Aug={}  Sus2={}  Sus4={}  \
+             m7b5={}  Dim7={}\n\n{}",
             failures.len(),
             counters.major,
             counters.minor,
@@ -227,6 +210,8 @@ fn test_chord_detector() {
             counters.augmented,
             counters.suspended_second,
             counters.suspended_fourth,
+            counters.half_diminished,
+            counters.diminished_seventh,
             "wow",
             //failures
             //    .into_iter()
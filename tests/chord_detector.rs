@@ -0,0 +1,1347 @@
+//! Tests for chord detection on synthetic chromagrams.
+
+use chord_detector::{
+    detect_chord_stateless, Chord, ChordDetector, ChordError, ChordKind, ChordTemplate,
+    DetectorConfig, NoteName, TemplateModel, ToneFit,
+};
+
+/// Build a chromagram with unit energy on each of `notes` (semitones from C).
+fn chroma_of(notes: &[usize]) -> [f32; 12] {
+    let mut chroma = [0.0; 12];
+    for &n in notes {
+        chroma[n % 12] = 1.0;
+    }
+    chroma
+}
+
+#[test]
+fn test_detect_with_applies_transform_before_scoring() {
+    let mut detector = ChordDetector::new();
+    // C major with a loud stray D
+    let mut chroma = chroma_of(&[0, 4, 7]);
+    chroma[2] = 3.0;
+
+    // Remove the stray D before scoring
+    let chord = detector
+        .detect_with(&chroma, |c| {
+            c[2] = 0.0;
+        })
+        .unwrap();
+    assert_eq!(chord.root, NoteName::C);
+    assert_eq!(chord.quality, ChordKind::Major);
+    // The caller's input is left untouched
+    assert_eq!(chroma[2], 3.0);
+}
+
+#[test]
+// The D in this chroma reads as a ninth once `extended_chords` is enabled
+#[cfg(not(feature = "extended_chords"))]
+fn test_seventh_bleed_favours_dominant_seventh() {
+    // C7 with a quiet seventh and G's third harmonic leaking into D
+    let mut chroma = chroma_of(&[0, 4, 7]);
+    chroma[10] = 0.05;
+    chroma[2] = 0.3;
+
+    let mut plain = ChordDetector::new();
+    let mut tuned = ChordDetector::builder().seventh_bleed(0.3).build();
+
+    assert_eq!(plain.detect_chord(&chroma).unwrap().quality, ChordKind::Major);
+    let chord = tuned.detect_chord(&chroma).unwrap();
+    assert_eq!(chord.root, NoteName::C);
+    assert_eq!(chord.quality, ChordKind::DominantSeventh);
+
+    // Triads are scored exactly as before
+    let mut triad = chroma_of(&[0, 4, 7]);
+    triad[1] = 0.2;
+    assert_eq!(
+        plain.detect_chord(&triad).unwrap(),
+        tuned.detect_chord(&triad).unwrap()
+    );
+}
+
+#[test]
+fn test_exact_confidence_preserves_ranking() {
+    let mut fast = ChordDetector::new();
+    let mut exact = ChordDetector::builder().exact_confidence(true).build();
+
+    let chromas = [
+        [0.9, 0.1, 0.2, 0.0, 0.8, 0.1, 0.0, 0.7, 0.1, 0.3, 0.0, 0.2],
+        [0.1, 0.0, 0.6, 0.1, 0.2, 0.9, 0.0, 0.1, 0.0, 0.8, 0.0, 0.1],
+        [0.3, 0.4, 0.2, 0.5, 0.1, 0.6, 0.2, 0.3, 0.7, 0.1, 0.4, 0.2],
+    ];
+    for chroma in &chromas {
+        let a = fast.top_k(chroma, 10).unwrap();
+        let b = exact.top_k(chroma, 10).unwrap();
+        for (x, y) in a.iter().zip(&b) {
+            assert_eq!((x.root, x.quality), (y.root, y.quality));
+            assert!((x.confidence - y.confidence).abs() < 1e-6);
+        }
+    }
+}
+
+#[test]
+fn test_is_chord_reports_near_misses() {
+    let mut detector = ChordDetector::new();
+    let mut chroma = chroma_of(&[0, 4, 7]);
+    chroma[1] = 0.1;
+
+    let c_major = Chord {
+        root: NoteName::C,
+        quality: ChordKind::Major,
+        confidence: 0.0,
+        bass: None,
+    };
+    let (hit, score) = detector.is_chord(&chroma, &c_major).unwrap();
+    assert!(hit);
+    assert_eq!(score, detector.detect_chord(&chroma).unwrap().confidence);
+
+    let a_minor = Chord {
+        root: NoteName::A,
+        quality: ChordKind::Minor,
+        ..c_major
+    };
+    let (hit, miss_score) = detector.is_chord(&chroma, &a_minor).unwrap();
+    assert!(!hit);
+    assert!(miss_score > score);
+}
+
+#[test]
+fn test_inversions_of_c_major() {
+    let with_bass = |bass| Chord {
+        root: NoteName::C,
+        quality: ChordKind::Major,
+        confidence: 0.0,
+        bass,
+    };
+
+    assert_eq!(with_bass(None).inversion(), None);
+    assert_eq!(with_bass(Some(NoteName::C)).inversion(), Some(0));
+    assert_eq!(with_bass(Some(NoteName::E)).inversion(), Some(1));
+    assert_eq!(with_bass(Some(NoteName::G)).inversion(), Some(2));
+    assert_eq!(with_bass(Some(NoteName::D)).inversion(), None);
+
+    assert_eq!(with_bass(Some(NoteName::C)).to_string(), "C");
+    assert_eq!(with_bass(Some(NoteName::E)).to_string(), "C/E");
+    assert_eq!(with_bass(Some(NoteName::G)).to_string(), "C/G");
+
+    let c7 = Chord {
+        quality: ChordKind::DominantSeventh,
+        ..with_bass(Some(NoteName::As))
+    };
+    assert_eq!(c7.inversion(), Some(3));
+    assert_eq!(c7.to_string(), "C7/A#");
+}
+
+#[test]
+fn test_stateless_matches_detector() {
+    let mut detector = ChordDetector::builder().bleed(0.2).build();
+    let chromas = [
+        [0.9, 0.1, 0.2, 0.0, 0.8, 0.1, 0.0, 0.7, 0.1, 0.3, 0.0, 0.2],
+        [0.1, 0.0, 0.6, 0.1, 0.2, 0.9, 0.0, 0.1, 0.0, 0.8, 0.0, 0.1],
+        [0.0, 0.7, 0.1, 0.0, 0.9, 0.0, 0.1, 0.0, 0.8, 0.0, 0.6, 0.2],
+    ];
+    for chroma in &chromas {
+        let stateful = detector.detect_chord(chroma).unwrap();
+        let stateless = detect_chord_stateless(chroma, 0.2).unwrap();
+        assert_eq!(stateful, stateless);
+    }
+
+    for bad in [-0.1, 5.0, f32::NAN] {
+        let result = detect_chord_stateless(&chromas[0], bad);
+        assert!(matches!(result, Err(ChordError::InvalidArgument { arg: "bleed", .. })));
+    }
+}
+
+#[test]
+// A-C-E with a stray D fits inside D9 once `extended_chords` is enabled
+#[cfg(not(feature = "extended_chords"))]
+fn test_ranked_grid_layout() {
+    let mut detector = ChordDetector::new();
+    let mut chroma = chroma_of(&[9, 0, 4]);
+    chroma[2] = 0.1;
+
+    let grid = detector.ranked_grid(&chroma).unwrap();
+    assert_eq!(grid.len(), 12);
+    assert!(grid.iter().all(|row| row.len() == 12));
+
+    let best = detector.detect_chord(&chroma).unwrap();
+    assert_eq!(best.quality, ChordKind::Minor); // second row
+    let (root, score) = grid[1][best.root as usize];
+    assert_eq!(root, best.root);
+    assert_eq!(score, best.confidence);
+}
+
+#[test]
+#[cfg(feature = "extended_chords")]
+fn test_ranked_grid_layout_extended() {
+    let mut detector = ChordDetector::new();
+    let mut chroma = chroma_of(&[9, 0, 4]);
+    chroma[11] = 0.1;
+
+    let grid = detector.ranked_grid(&chroma).unwrap();
+    assert_eq!(grid.len(), 16);
+    assert!(grid.iter().all(|row| row.len() == 12));
+
+    let best = detector.detect_chord(&chroma).unwrap();
+    assert_eq!(best.quality, ChordKind::Minor); // second row
+    assert_eq!(grid[1][best.root as usize], (best.root, best.confidence));
+}
+
+#[test]
+fn test_silence_reset_forgets_previous_chord() {
+    let mut detector = ChordDetector::builder().silence_reset_frames(3).build();
+    let mut a_minor = chroma_of(&[9, 0, 4]);
+    a_minor[2] = 0.1;
+    let mut f_major = chroma_of(&[5, 9, 0]);
+    f_major[7] = 0.2;
+
+    for _ in 0..5 {
+        detector.detect_chord(&a_minor).unwrap();
+    }
+    for _ in 0..3 {
+        detector.detect_chord(&[0.0; 12]).unwrap();
+    }
+    let after_pause = detector.detect_chord(&f_major).unwrap();
+    let fresh = ChordDetector::new().detect_chord(&f_major).unwrap();
+    assert_eq!(after_pause, fresh);
+}
+
+#[test]
+fn test_equivalent_spellings() {
+    let chord = |root, quality| Chord {
+        root,
+        quality,
+        confidence: 0.1,
+        bass: None,
+    };
+
+    let aug = chord(NoteName::C, ChordKind::Augmented).equivalent_spellings();
+    let roots: Vec<_> = aug.iter().map(|c| c.root).collect();
+    assert_eq!(roots, vec![NoteName::C, NoteName::E, NoteName::Gs]);
+
+    let sus = chord(NoteName::C, ChordKind::SuspendedSecond).equivalent_spellings();
+    assert_eq!(sus[1], chord(NoteName::G, ChordKind::SuspendedFourth));
+
+    let major = chord(NoteName::Ds, ChordKind::Major);
+    assert_eq!(major.equivalent_spellings(), vec![major]);
+}
+
+#[test]
+fn test_root_confidence_ignores_same_root_qualities() {
+    let mut detector = ChordDetector::new();
+
+    // C7: C major and Cmaj7 compete, but no other root explains all four tones
+    let (root, gap) = detector.root_confidence(&chroma_of(&[0, 4, 7, 10])).unwrap();
+    assert_eq!(root, NoteName::C);
+    assert!(gap > 0.05);
+
+    // A bare C triad fits Am7 with a missing A just as well
+    let (_, ambiguous_gap) = detector.root_confidence(&chroma_of(&[0, 4, 7])).unwrap();
+    assert!(ambiguous_gap < 1e-6);
+}
+
+#[test]
+// The D in this chroma reads as a ninth once `extended_chords` is enabled
+#[cfg(not(feature = "extended_chords"))]
+fn test_missing_note_tolerance_forgives_absent_fifth() {
+    // C major with a muted fifth
+    let mut chroma = chroma_of(&[0, 4]);
+    chroma[7] = 0.02;
+    chroma[2] = 0.1;
+
+    let mut strict = ChordDetector::builder().missing_note_tolerance(0).build();
+    let mut tolerant = ChordDetector::builder().missing_note_tolerance(1).build();
+
+    let chord = tolerant.detect_chord(&chroma).unwrap();
+    assert_eq!((chord.root, chord.quality), (NoteName::C, ChordKind::Major));
+
+    let c_major = Chord { confidence: 0.0, ..chord };
+    let (_, strict_score) = strict.is_chord(&chroma, &c_major).unwrap();
+    assert!(strict_score > chord.confidence);
+}
+
+#[test]
+fn test_min_match_quality_rejects_noise() {
+    let mut detector = ChordDetector::builder().min_match_quality(0.7).build();
+
+    let chord = detector.detect_chord(&chroma_of(&[0, 4, 7])).unwrap();
+    assert_eq!((chord.root, chord.quality), (NoteName::C, ChordKind::Major));
+
+    // Deterministic noise spread over every bin
+    let mut state = 0x2545_f491_u32;
+    let mut noise = [0.0; 12];
+    for bin in &mut noise {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        *bin = 0.5 + 0.5 * (state as f32 / u32::MAX as f32);
+    }
+    let chord = detector.detect_chord(&noise).unwrap();
+    assert_eq!((chord.root, chord.quality), (NoteName::Unknown, ChordKind::Unknown));
+    assert_eq!(chord.to_string(), "N.C.");
+
+    // Without the threshold the least-bad chord is still reported
+    let chord = ChordDetector::new().detect_chord(&noise).unwrap();
+    assert_ne!(chord.quality, ChordKind::Unknown);
+
+    for bad in [-0.1, 1.5, f32::NAN] {
+        let result = ChordDetector::builder().min_match_quality(bad).try_build();
+        assert!(matches!(
+            result,
+            Err(ChordError::InvalidArgument { arg: "min_match_quality", .. })
+        ));
+    }
+}
+
+#[test]
+fn test_detect_chord_opt_silence() {
+    let mut detector = ChordDetector::new();
+    assert_eq!(detector.detect_chord_opt(&[0.0; 12]).unwrap(), None);
+    assert_eq!(detector.detect_chord_opt(&[0.001; 12]).unwrap(), None);
+
+    let chord = detector.detect_chord_opt(&chroma_of(&[0, 4, 7])).unwrap().unwrap();
+    assert_eq!((chord.root, chord.quality), (NoteName::C, ChordKind::Major));
+
+    let mut strict = ChordDetector::builder().silence_threshold(10.0).build();
+    assert_eq!(strict.detect_chord_opt(&chroma_of(&[0, 4, 7])).unwrap(), None);
+    assert!(strict.detect_chord_opt(&[0.0; 11]).is_err());
+}
+
+#[test]
+fn test_normalized_confidence() {
+    let mut detector = ChordDetector::builder().normalized_confidence(true).build();
+
+    for level in [0.01, 1.0, 250.0] {
+        let chroma = chroma_of(&[0, 4, 7]).map(|c| c * level);
+        let chord = detector.detect_chord(&chroma).unwrap();
+        assert_eq!((chord.root, chord.quality), (NoteName::C, ChordKind::Major));
+        assert!((chord.confidence - 1.0).abs() < 1e-5, "level {level}: {}", chord.confidence);
+    }
+
+    // A stray note lowers the similarity, the same way at any level
+    let mut muddy = chroma_of(&[0, 4, 7]);
+    muddy[1] = 0.5;
+    let quiet = detector.detect_chord(&muddy.map(|c| c * 0.1)).unwrap().confidence;
+    let loud = detector.detect_chord(&muddy.map(|c| c * 10.0)).unwrap().confidence;
+    assert!(quiet < 0.99 && quiet > 0.0);
+    assert!((quiet - loud).abs() < 1e-4);
+
+    // Ranking matches raw mode
+    let top = detector.top_k(&muddy, 3).unwrap();
+    let raw = ChordDetector::new().top_k(&muddy, 3).unwrap();
+    for (a, b) in top.iter().zip(&raw) {
+        assert_eq!((a.root, a.quality), (b.root, b.quality));
+    }
+    assert!(detector.is_chord(&muddy, &top[0]).unwrap().0);
+}
+
+#[test]
+fn test_custom_templates() {
+    let mut detector = ChordDetector::builder()
+        .templates(vec![
+            ChordTemplate::new("", &[0, 4, 7], 1.06),
+            ChordTemplate::new("add9", &[0, 4, 7, 14], 1.06),
+        ])
+        .build();
+
+    let chord = detector.detect_chord(&chroma_of(&[2, 6, 9, 4])).unwrap();
+    assert_eq!((chord.root, chord.quality), (NoteName::D, ChordKind::Custom(1)));
+    assert_eq!(detector.template(chord.quality).unwrap().name, "add9");
+    assert_eq!(detector.chord_name(&chord), "Dadd9");
+    assert_eq!(chord.to_string(), "D(custom 1)");
+
+    let chord = detector.detect_chord(&chroma_of(&[2, 6, 9])).unwrap();
+    assert_eq!((chord.root, chord.quality), (NoteName::D, ChordKind::Custom(0)));
+
+    // Only the custom shapes are scored
+    assert_eq!(detector.top_k(&chroma_of(&[0, 4, 7]), 100).unwrap().len(), 24);
+    assert_eq!(detector.ranked_grid(&chroma_of(&[0, 4, 7])).unwrap().len(), 2);
+    let c_major = Chord {
+        root: NoteName::C,
+        quality: ChordKind::Major,
+        confidence: 0.0,
+        bass: None,
+    };
+    assert!(detector.is_chord(&chroma_of(&[0, 4, 7]), &c_major).is_err());
+
+    let triad = ChordTemplate::new("", &[0, 4, 7], 1.0);
+    let invalid = [
+        vec![ChordTemplate::new("", &[0, 4, 7], 0.0)],
+        vec![ChordTemplate::new("", &[0, 4, 7], f32::NAN)],
+        vec![ChordTemplate::new("", &[], 1.0)],
+        vec![ChordTemplate::new("", &(0..12).collect::<Vec<_>>(), 1.0)],
+        vec![triad; 65537],
+    ];
+    for templates in invalid {
+        let result = ChordDetector::builder().templates(templates).try_build();
+        assert!(matches!(result, Err(ChordError::InvalidArgument { arg: "templates", .. })));
+    }
+
+    // Built-in detectors are unaffected
+    let chord = ChordDetector::new().detect_chord(&chroma_of(&[2, 6, 9, 4])).unwrap();
+    assert!(!matches!(chord.quality, ChordKind::Custom(_)));
+    let all = ChordDetector::new().top_k(&chroma_of(&[0, 4, 7]), 1000).unwrap();
+    assert_eq!(all.len(), 12 * ChordKind::ALL.len());
+}
+
+#[test]
+fn test_detect_bass_reports_emphasized_third() {
+    let mut detector = ChordDetector::builder().detect_bass(true).build();
+
+    let mut first_inversion = chroma_of(&[0, 4, 7]);
+    first_inversion[4] = 2.0; // E
+    let chord = detector.detect_chord(&first_inversion).unwrap();
+    assert_eq!((chord.root, chord.quality), (NoteName::C, ChordKind::Major));
+    assert_eq!(chord.bass, Some(NoteName::E));
+    assert_eq!(chord.inversion(), Some(1));
+    assert_eq!(chord.to_string(), "C/E");
+
+    let mut root_position = chroma_of(&[0, 4, 7]);
+    root_position[0] = 2.0;
+    let chord = detector.detect_chord(&root_position).unwrap();
+    assert_eq!(chord.bass, Some(NoteName::C));
+    assert_eq!(chord.to_string(), "C");
+
+    // Off by default
+    let chord = ChordDetector::new().detect_chord(&first_inversion).unwrap();
+    assert_eq!(chord.bass, None);
+}
+
+#[test]
+fn test_note_name_parse_and_display() {
+    for idx in 0..12 {
+        let note: NoteName = ["C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B"][idx]
+            .parse()
+            .unwrap();
+        assert_eq!(note.to_semitone(), Some(idx));
+        assert_eq!(note.to_string().parse::<NoteName>().unwrap(), note);
+    }
+    assert_eq!(NoteName::Unknown.to_semitone(), None);
+    assert_eq!(NoteName::Unknown.to_string().parse::<NoteName>().unwrap(), NoteName::Unknown);
+
+    assert_eq!("Db".parse::<NoteName>().unwrap(), NoteName::Cs);
+    assert_eq!("c#".parse::<NoteName>().unwrap(), NoteName::Cs);
+    assert_eq!(" bb ".parse::<NoteName>().unwrap(), NoteName::As);
+    assert_eq!("E♭".parse::<NoteName>().unwrap(), NoteName::Ds);
+    assert_eq!("Cb".parse::<NoteName>().unwrap(), NoteName::B);
+    assert_eq!(NoteName::Fs.to_string(), "F#");
+    for bad in ["", "H", "C##", "Cx", "Do"] {
+        assert!(bad.parse::<NoteName>().is_err(), "{bad:?}");
+    }
+}
+
+#[test]
+fn test_note_name_all_order_and_next() {
+    for (idx, note) in NoteName::ALL.iter().enumerate() {
+        assert_eq!(note.to_semitone(), Some(idx));
+        assert_eq!(note.next(), NoteName::ALL[(idx + 1) % 12]);
+    }
+    assert_eq!(NoteName::B.next(), NoteName::C);
+    assert_eq!(NoteName::Unknown.next(), NoteName::Unknown);
+
+    let mut shuffled = vec![NoteName::Unknown];
+    shuffled.extend((0..12).map(|i| NoteName::ALL[(i * 5) % 12]));
+    shuffled.sort();
+    assert_eq!(shuffled[..12], NoteName::ALL);
+    assert_eq!(shuffled[12], NoteName::Unknown);
+}
+
+#[test]
+fn test_detect_chord_smoothed_holds_dominant_chord() {
+    let c_major = chroma_of(&[0, 4, 7]);
+    let a_minor = chroma_of(&[9, 0, 4]);
+
+    let mut raw = ChordDetector::new();
+    let mut smoothed = ChordDetector::builder().smoothing_window(5).build();
+    let pattern = [c_major, a_minor, c_major, c_major, a_minor];
+
+    let mut raw_changes = 0;
+    let mut previous = None;
+    for (i, frame) in pattern.iter().cycle().take(30).enumerate() {
+        let r = raw.detect_chord(frame).unwrap();
+        if previous.is_some_and(|p: NoteName| p != r.root) {
+            raw_changes += 1;
+        }
+        previous = Some(r.root);
+
+        let s = smoothed.detect_chord_smoothed(frame).unwrap();
+        if i >= 2 {
+            assert_eq!((s.root, s.quality), (NoteName::C, ChordKind::Major), "frame {i}");
+        }
+    }
+    assert!(raw_changes > 10);
+
+    // Window 1 passes detections straight through
+    let mut passthrough = ChordDetector::new();
+    let chord = passthrough.detect_chord_smoothed(&a_minor).unwrap();
+    assert_eq!((chord.root, chord.quality), (NoteName::A, ChordKind::Minor));
+}
+
+#[test]
+fn test_score_all_matches_detect_chord() {
+    let mut detector = ChordDetector::new();
+    let mut chroma = chroma_of(&[7, 11, 2, 5]);
+    chroma[9] = 0.2;
+
+    let best = detector.detect_chord(&chroma).unwrap();
+    let scores = detector.score_all(&chroma).unwrap().to_vec();
+    assert_eq!(scores.len(), 12 * ChordKind::ALL.len());
+
+    let (idx, &min) = scores
+        .iter()
+        .enumerate()
+        .min_by(|a, b| a.1.total_cmp(b.1))
+        .unwrap();
+    assert_eq!(min, best.confidence);
+    assert_eq!(detector.chord_at(idx), Some((best.root, best.quality)));
+    #[cfg(not(feature = "extended_chords"))]
+    assert_eq!(detector.chord_at(3 * 12 + 7), Some((NoteName::G, ChordKind::DominantSeventh)));
+    // the sixth chords are scored before the sevenths
+    #[cfg(feature = "extended_chords")]
+    assert_eq!(detector.chord_at(5 * 12 + 7), Some((NoteName::G, ChordKind::DominantSeventh)));
+    assert_eq!(detector.chord_at(scores.len()), None);
+}
+
+#[test]
+fn test_non_finite_chroma_is_rejected() {
+    let mut detector = ChordDetector::new();
+    let mut chroma = chroma_of(&[0, 4, 7]);
+    chroma[5] = f32::NAN;
+    assert!(matches!(detector.detect_chord(&chroma), Err(ChordError::NonFinite { index: 5 })));
+    chroma[5] = f32::INFINITY;
+    assert!(matches!(detector.top_k(&chroma, 3), Err(ChordError::NonFinite { index: 5 })));
+
+    // NaN introduced by a transform is ranked last instead of panicking
+    assert!(detector.detect_with(&chroma_of(&[0, 4, 7]), |c| c[5] = f32::NAN).is_ok());
+    assert_eq!(detector.detect_chord(&chroma_of(&[0, 4, 7])).unwrap().root, NoteName::C);
+}
+
+#[test]
+fn test_chord_name_for_every_quality() {
+    let chord = |root, quality| Chord {
+        root,
+        quality,
+        confidence: 0.0,
+        bass: Some(NoteName::E),
+    };
+    let expected = [
+        (ChordKind::Major, "C", "F#"),
+        (ChordKind::Minor, "Cm", "F#m"),
+        (ChordKind::PowerFifth, "C5", "F#5"),
+        (ChordKind::DominantSeventh, "C7", "F#7"),
+        (ChordKind::MajorSeventh, "Cmaj7", "F#maj7"),
+        (ChordKind::MinorSeventh, "Cm7", "F#m7"),
+        (ChordKind::Diminished, "Cdim", "F#dim"),
+        (ChordKind::Augmented, "Caug", "F#aug"),
+        (ChordKind::SuspendedSecond, "Csus2", "F#sus2"),
+        (ChordKind::SuspendedFourth, "Csus4", "F#sus4"),
+        (ChordKind::HalfDiminished, "Cm7b5", "F#m7b5"),
+        (ChordKind::DiminishedSeventh, "Cdim7", "F#dim7"),
+    ];
+    for (quality, on_c, on_f_sharp) in expected {
+        assert_eq!(chord(NoteName::C, quality).name(), on_c);
+        assert_eq!(chord(NoteName::Fs, quality).name(), on_f_sharp);
+    }
+
+    // The bass only shows up in Display
+    assert_eq!(chord(NoteName::C, ChordKind::Major).to_string(), "C/E");
+    assert_eq!(chord(NoteName::Unknown, ChordKind::Unknown).name(), "N.C.");
+}
+
+#[test]
+fn test_midi_notes() {
+    let chord = |root, quality| Chord {
+        root,
+        quality,
+        confidence: 0.0,
+        bass: None,
+    };
+    assert_eq!(chord(NoteName::C, ChordKind::Major).midi_notes(4), [60, 64, 67]);
+    assert_eq!(
+        chord(NoteName::G, ChordKind::DominantSeventh).midi_notes(3),
+        [55, 59, 62, 65]
+    );
+
+    // G9 is note 127, so only the root fits
+    assert_eq!(chord(NoteName::G, ChordKind::Major).midi_notes(9), [127]);
+    assert!(chord(NoteName::C, ChordKind::Major).midi_notes(12).is_empty());
+    assert!(chord(NoteName::Unknown, ChordKind::Unknown).midi_notes(4).is_empty());
+}
+
+#[test]
+fn test_pitch_classes_follow_intervals() {
+    let chord = |root, quality| Chord {
+        root,
+        quality,
+        confidence: 0.0,
+        bass: None,
+    };
+    assert_eq!(ChordKind::Major.intervals(), [0, 4, 7]);
+    assert_eq!(
+        chord(NoteName::C, ChordKind::Major).pitch_classes(),
+        [NoteName::C, NoteName::E, NoteName::G]
+    );
+
+    // Wraps past B and keeps the interval order
+    let g7 = chord(NoteName::G, ChordKind::DominantSeventh);
+    assert_eq!(
+        g7.pitch_classes(),
+        [NoteName::G, NoteName::B, NoteName::D, NoteName::F]
+    );
+    let from_intervals: Vec<_> = ChordKind::DominantSeventh
+        .intervals()
+        .iter()
+        .map(|&i| NoteName::ALL[(7 + i) % 12])
+        .collect();
+    assert_eq!(g7.pitch_classes(), from_intervals);
+
+    assert!(chord(NoteName::Unknown, ChordKind::Unknown).pitch_classes().is_empty());
+    assert!(ChordKind::Custom(0).intervals().is_empty());
+}
+
+#[test]
+fn test_qualities_restricts_scored_kinds() {
+    let dominant = chroma_of(&[0, 4, 7, 10]);
+    let mut full = ChordDetector::new();
+    assert_eq!(full.detect_chord(&dominant).unwrap().quality, ChordKind::DominantSeventh);
+
+    let mut triads = ChordDetector::builder()
+        .qualities(&[ChordKind::Minor, ChordKind::Major])
+        .build();
+    let chord = triads.detect_chord(&dominant).unwrap();
+    assert_eq!((chord.root, chord.quality), (NoteName::C, ChordKind::Major));
+    assert!(triads
+        .top_k(&dominant, 30)
+        .unwrap()
+        .iter()
+        .all(|c| matches!(c.quality, ChordKind::Major | ChordKind::Minor)));
+    assert_eq!(triads.score_all(&dominant).unwrap().len(), 24);
+    assert_eq!(triads.chord_at(12), Some((NoteName::C, ChordKind::Minor)));
+
+    for bad in [&[][..], &[ChordKind::Unknown][..]] {
+        let result = ChordDetector::builder().qualities(bad).try_build();
+        assert!(matches!(result, Err(ChordError::InvalidArgument { arg: "qualities", .. })));
+    }
+}
+
+#[test]
+fn test_try_build_rejects_invalid_bleed() {
+    for bad in [-0.1, 1.5, f32::NAN] {
+        let result = ChordDetector::builder().bleed(bad).try_build();
+        assert!(matches!(result, Err(ChordError::InvalidArgument { arg: "bleed", .. })));
+        let result = ChordDetector::builder().seventh_bleed(bad).try_build();
+        assert!(matches!(result, Err(ChordError::InvalidArgument { arg: "seventh_bleed", .. })));
+    }
+    for ok in [0.0, 0.5, 1.0] {
+        assert!(ChordDetector::builder().bleed(ok).try_build().is_ok());
+    }
+}
+
+#[test]
+fn test_build_repairs_invalid_settings() {
+    let config = ChordDetector::builder()
+        .bleed(1.2)
+        .seventh_bleed(f32::NAN)
+        .weight_bass(-0.5)
+        .silence_threshold(-1.0)
+        .smoothing_alpha(0.0)
+        .qualities(&[])
+        .build()
+        .config();
+    let expected = ChordDetector::builder().bleed(1.0).build().config();
+    assert_eq!(config, expected);
+
+    let config = ChordDetector::builder().bleed(f32::NAN).build().config();
+    assert_eq!(config, ChordDetector::new().config());
+
+    let mut repaired = ChordDetector::builder()
+        .templates(vec![ChordTemplate::new("", &[], 1.0)])
+        .build();
+    let chord = repaired.detect_chord(&chroma_of(&[0, 4, 7])).unwrap();
+    assert_eq!((chord.root, chord.quality), (NoteName::C, ChordKind::Major));
+}
+
+#[test]
+fn test_try_build_rejects_invalid_silence_threshold() {
+    for bad in [-1.0, f32::NAN, f32::INFINITY] {
+        let result = ChordDetector::builder().silence_threshold(bad).try_build();
+        assert!(matches!(
+            result,
+            Err(ChordError::InvalidArgument { arg: "silence_threshold", .. })
+        ));
+    }
+    assert!(ChordDetector::builder().silence_threshold(0.0).try_build().is_ok());
+}
+
+#[test]
+// The D in this chroma reads as a ninth once `extended_chords` is enabled
+#[cfg(not(feature = "extended_chords"))]
+fn test_harmonic_templates_absorb_overtones() {
+    // C major on a bright instrument: strong third partials of E (B) and
+    // G (D), and a weaker fifth partial of E (G#)
+    let mut chroma = chroma_of(&[0, 4, 7]);
+    chroma[11] = 0.45;
+    chroma[2] = 0.45;
+    chroma[8] = 0.2;
+
+    let detect = |model| {
+        let mut detector = ChordDetector::builder().template_model(model).build();
+        detector.detect_chord(&chroma).unwrap()
+    };
+    let binary = detect(TemplateModel::Binary);
+    assert_eq!((binary.root, binary.quality), (NoteName::C, ChordKind::MajorSeventh));
+    let harmonic = detect(TemplateModel::Harmonic);
+    assert_eq!((harmonic.root, harmonic.quality), (NoteName::C, ChordKind::Major));
+    assert!(harmonic.confidence < binary.confidence);
+}
+
+#[test]
+fn test_bleed_interval_moves_suppressed_bin() {
+    // C with an equally strong F and G: whichever of the two C's bleed is
+    // subtracted from drops out
+    let mut chroma = [0.0; 12];
+    chroma[0] = 1.0;
+    chroma[5] = 0.5;
+    chroma[7] = 0.5;
+    let detect = |interval| {
+        let mut detector = ChordDetector::builder().bleed(0.5).bleed_interval(interval).build();
+        detector.detect_chord(&chroma).unwrap()
+    };
+
+    // Default: a fourth down from C is G, leaving C and F
+    assert_eq!(detect(-5).root, NoteName::F);
+    // A fourth up from C is F, so F can no longer be the root
+    assert_ne!(detect(5).root, NoteName::F);
+    // Offsets are pitch classes: a fifth up is the same bin as a fourth down
+    assert_eq!(detect(7), detect(-5));
+    assert_eq!(detect(-7), detect(5));
+
+    for bad in [-12, 12] {
+        let result = ChordDetector::builder().bleed_interval(bad).try_build();
+        assert!(matches!(result, Err(ChordError::InvalidArgument { arg: "bleed_interval", .. })));
+    }
+}
+
+#[test]
+// B-D-F-A is also Dm6, which wins the tie once `extended_chords` is enabled
+#[cfg(not(feature = "extended_chords"))]
+fn test_half_diminished_and_diminished_seventh() {
+    let mut detector = ChordDetector::new();
+    let chord = |root, quality| Chord {
+        root,
+        quality,
+        confidence: 0.0,
+        bass: None,
+    };
+
+    // B-D-F-A, with the seventh a little quieter than the triad
+    let mut chroma = chroma_of(&[11, 2, 5]);
+    chroma[9] = 0.7;
+    let best = detector.detect_chord(&chroma).unwrap();
+    assert_eq!((best.root, best.quality), (NoteName::B, ChordKind::HalfDiminished));
+    assert_eq!(best.name(), "Bm7b5");
+    for rival in [
+        chord(NoteName::B, ChordKind::Diminished),
+        chord(NoteName::B, ChordKind::MinorSeventh),
+    ] {
+        let (_, score) = detector.is_chord(&chroma, &rival).unwrap();
+        assert!(score > best.confidence, "{rival} scored {score}");
+    }
+
+    // C-Eb-Gb-A is symmetric: any of its tones names the same chord
+    let dim7 = detector.detect_chord(&chroma_of(&[0, 3, 6, 9])).unwrap();
+    assert_eq!(dim7.quality, ChordKind::DiminishedSeventh);
+    assert_eq!(dim7.equivalent_spellings().len(), 4);
+}
+
+#[test]
+fn test_detect_segments_merges_runs() {
+    let mut c_major = chroma_of(&[0, 4, 7]);
+    let a_minor = chroma_of(&[9, 0, 4]);
+    let mut chromas = vec![c_major; 3];
+    // A slightly different C major still extends the run
+    c_major[2] = 0.05;
+    chromas[1] = c_major;
+    chromas.extend([a_minor; 2]);
+
+    let mut detector = ChordDetector::new();
+    let chords = detector.detect_sequence(&chromas).unwrap();
+    assert_eq!(chords.len(), 5);
+
+    let segments = detector.detect_segments(&chromas).unwrap();
+    let spans: Vec<_> = segments.iter().map(|(c, s, e)| (c.root, c.quality, *s, *e)).collect();
+    assert_eq!(
+        spans,
+        [(NoteName::C, ChordKind::Major, 0, 3), (NoteName::A, ChordKind::Minor, 3, 5)]
+    );
+    assert_eq!(segments[0].0, chords[0]);
+    assert!(detector.detect_segments(&[]).unwrap().is_empty());
+}
+
+#[test]
+fn test_chord_kind_shorthand_round_trips() {
+    let kinds = [
+        ChordKind::Major,
+        ChordKind::Minor,
+        ChordKind::PowerFifth,
+        ChordKind::DominantSeventh,
+        ChordKind::MajorSeventh,
+        ChordKind::MinorSeventh,
+        ChordKind::Diminished,
+        ChordKind::Augmented,
+        ChordKind::SuspendedSecond,
+        ChordKind::SuspendedFourth,
+        ChordKind::HalfDiminished,
+        ChordKind::DiminishedSeventh,
+        ChordKind::Unknown,
+    ];
+    for kind in kinds {
+        assert_eq!(ChordKind::try_from(kind.shorthand()).unwrap(), kind);
+        let upper = kind.shorthand().to_uppercase();
+        assert_eq!(ChordKind::try_from(upper.as_str()).unwrap(), kind);
+    }
+
+    let aliases = [
+        ("m", ChordKind::Minor),
+        ("5", ChordKind::PowerFifth),
+        ("Dom7", ChordKind::DominantSeventh),
+        (" min7 ", ChordKind::MinorSeventh),
+    ];
+    for (text, kind) in aliases {
+        assert_eq!(ChordKind::try_from(text).unwrap(), kind);
+    }
+
+    assert!(matches!(
+        ChordKind::try_from("maj9"),
+        Err(ChordError::InvalidArgument { arg: "quality", .. })
+    ));
+    assert_eq!(ChordKind::Custom(0).shorthand(), "?");
+}
+
+#[test]
+fn test_from_config_rebuilds_identical_detector() {
+    let chromas = [
+        chroma_of(&[0, 4, 7]),
+        chroma_of(&[9, 0, 4, 7]),
+        chroma_of(&[2, 5, 9, 0]),
+        chroma_of(&[7, 11, 2, 5]),
+        [0.3, 0.1, 0.5, 0.0, 0.9, 0.2, 0.0, 0.7, 0.1, 0.4, 0.0, 0.2],
+    ];
+    let configured = || {
+        ChordDetector::builder()
+            .bleed(0.2)
+            .bleed_interval(5)
+            .seventh_bleed(0.3)
+            .qualities(&[ChordKind::MinorSeventh, ChordKind::Major, ChordKind::DominantSeventh])
+            .template_model(TemplateModel::Harmonic)
+            .missing_note_tolerance(1)
+            .allow_rootless(true)
+            .weight_bass(0.5)
+            .normalized_confidence(true)
+            .detect_bass(true)
+            .smoothing_window(3)
+            .smoothing_alpha(0.5)
+            .build()
+    };
+    let custom = || {
+        ChordDetector::builder()
+            .templates(vec![ChordTemplate::new("add9", &[0, 4, 7, 2], 1.0)])
+            .min_match_quality(0.5)
+            .min_confidence(0.6)
+            .build()
+    };
+
+    for mut original in [ChordDetector::new(), configured(), custom()] {
+        let config = original.config();
+        let mut rebuilt = ChordDetector::from_config(config.clone());
+        assert_eq!(rebuilt.config(), config);
+        for chroma in &chromas {
+            assert_eq!(
+                rebuilt.detect_chord(chroma).unwrap(),
+                original.detect_chord(chroma).unwrap()
+            );
+            assert_eq!(
+                rebuilt.detect_chord_smoothed(chroma).unwrap(),
+                original.detect_chord_smoothed(chroma).unwrap()
+            );
+        }
+    }
+
+    assert_eq!(ChordDetector::new().config(), DetectorConfig::default());
+    assert_eq!(ChordDetector::new().config().qualities, None);
+    assert_eq!(
+        configured().config().qualities,
+        Some(vec![ChordKind::Major, ChordKind::DominantSeventh, ChordKind::MinorSeventh])
+    );
+}
+
+#[test]
+fn test_detect_pitch() {
+    let mut detector = ChordDetector::new();
+
+    // A with a weaker fifth from its third harmonic
+    let mut melody = [0.0; 12];
+    melody[9] = 1.0;
+    melody[4] = 0.25;
+    let (note, strength) = detector.detect_pitch(&melody).unwrap();
+    assert_eq!(note, NoteName::A);
+    assert!((strength - 0.8).abs() < 1e-6);
+
+    let (note, strength) = detector.detect_pitch(&[0.5; 12]).unwrap();
+    assert_eq!(note, NoteName::Unknown);
+    assert!((strength - 1.0 / 12.0).abs() < 1e-6);
+
+    let (note, strength) = detector.detect_pitch(&chroma_of(&[0, 4, 7])).unwrap();
+    assert_eq!(note, NoteName::Unknown);
+    assert!((strength - 1.0 / 3.0).abs() < 1e-6);
+
+    // Negative bins don't inflate the share past 1.0
+    let mut single = [0.0; 12];
+    single[2] = 1.0;
+    single[5] = -0.5;
+    assert_eq!(detector.detect_pitch(&single).unwrap(), (NoteName::D, 1.0));
+
+    assert_eq!(detector.detect_pitch(&[0.0; 12]).unwrap(), (NoteName::Unknown, 0.0));
+    assert!(matches!(
+        detector.detect_pitch(&[1.0; 11]),
+        Err(ChordError::InvalidLength { expected: 12, got: 11 })
+    ));
+}
+
+#[test]
+fn test_silent_chroma_is_unknown() {
+    let mut detector = ChordDetector::new();
+    let chord = detector.detect_chord(&[0.0; 12]).unwrap();
+    assert_eq!((chord.root, chord.quality), (NoteName::Unknown, ChordKind::Unknown));
+    assert_eq!(chord.confidence, f32::INFINITY);
+    assert_eq!(detector.top_k(&[0.0; 12], 5).unwrap(), [chord]);
+
+    // Below the default threshold of 1e-4 counts as silence too
+    let chord = detector.detect_chord(&[0.002; 12]).unwrap();
+    assert_eq!(chord.root, NoteName::Unknown);
+
+    let mut normalized = ChordDetector::builder().normalized_confidence(true).build();
+    assert_eq!(normalized.detect_chord(&[0.0; 12]).unwrap().confidence, 0.0);
+
+    // Silence never passes for a match on the other detection paths
+    let a_minor = Chord {
+        root: NoteName::A,
+        quality: ChordKind::Minor,
+        confidence: 0.0,
+        bass: None,
+    };
+    assert_eq!(detector.is_chord(&[0.0; 12], &a_minor).unwrap(), (false, f32::INFINITY));
+    assert_eq!(normalized.is_chord(&[0.0; 12], &a_minor).unwrap(), (false, 0.0));
+    assert_eq!(detector.root_confidence(&[0.0; 12]).unwrap(), (NoteName::Unknown, 0.0));
+    let silent = detector.detect_chord(&[0.0; 12]).unwrap();
+    assert_eq!(detect_chord_stateless(&[0.0; 12], 0.2).unwrap(), silent);
+}
+
+/// Noisy weighted chord chromas over a mix of roots and qualities.
+#[cfg(not(feature = "extended_chords"))]
+fn regression_battery() -> Vec<[f32; 12]> {
+    let kinds = [
+        ChordKind::Major,
+        ChordKind::Minor,
+        ChordKind::DominantSeventh,
+        ChordKind::MinorSeventh,
+        ChordKind::MajorSeventh,
+        ChordKind::Diminished,
+        ChordKind::SuspendedFourth,
+        ChordKind::HalfDiminished,
+    ];
+    let mut state = 0x2545_f491_u32;
+    let mut noise = move || {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        state as f32 / u32::MAX as f32
+    };
+    (0..24)
+        .map(|i| {
+            let root = i * 7 % 12;
+            let mut chroma = [0.0; 12];
+            for bin in chroma.iter_mut() {
+                *bin = 0.05 * noise();
+            }
+            for (j, &off) in kinds[i % kinds.len()].intervals().iter().enumerate() {
+                chroma[(root + off) % 12] += 1.0 - 0.15 * j as f32;
+            }
+            chroma
+        })
+        .collect()
+}
+
+#[test]
+// Pins the default quality set; `extended_chords` adds qualities that win
+// some of these frames
+#[cfg(not(feature = "extended_chords"))]
+fn test_default_detector_output_is_pinned() {
+    // Any change to profile layout, scoring or ranking shows up here
+    let expected = [
+        (NoteName::C, ChordKind::Major, 0.006984),
+        (NoteName::E, ChordKind::HalfDiminished, 0.008569),
+        (NoteName::D, ChordKind::DominantSeventh, 0.007064),
+        (NoteName::A, ChordKind::MinorSeventh, 0.008101),
+        (NoteName::E, ChordKind::MajorSeventh, 0.009997),
+        (NoteName::G, ChordKind::DominantSeventh, 0.003395),
+        (NoteName::B, ChordKind::SuspendedSecond, 0.010123),
+        (NoteName::Cs, ChordKind::HalfDiminished, 0.007266),
+        (NoteName::Gs, ChordKind::DominantSeventh, 0.008005),
+        (NoteName::Ds, ChordKind::Minor, 0.007340),
+        (NoteName::As, ChordKind::DominantSeventh, 0.008085),
+        (NoteName::F, ChordKind::MinorSeventh, 0.007150),
+        (NoteName::C, ChordKind::MajorSeventh, 0.006308),
+        (NoteName::Ds, ChordKind::DominantSeventh, 0.008518),
+        (NoteName::G, ChordKind::SuspendedSecond, 0.009357),
+        (NoteName::A, ChordKind::HalfDiminished, 0.006476),
+        (NoteName::Cs, ChordKind::MinorSeventh, 0.006281),
+        (NoteName::Gs, ChordKind::HalfDiminished, 0.007137),
+        (NoteName::Fs, ChordKind::DominantSeventh, 0.006688),
+        (NoteName::Cs, ChordKind::MinorSeventh, 0.008779),
+        (NoteName::Gs, ChordKind::MajorSeventh, 0.005945),
+        (NoteName::Ds, ChordKind::Diminished, 0.007103),
+        (NoteName::Ds, ChordKind::SuspendedSecond, 0.007112),
+        (NoteName::F, ChordKind::HalfDiminished, 0.005679),
+    ];
+
+    let mut detector = ChordDetector::new();
+    assert_eq!(detector.score_all(&[0.5; 12]).unwrap().len(), 144);
+    for (chroma, (root, quality, confidence)) in regression_battery().iter().zip(expected) {
+        let chord = detector.detect_chord(chroma).unwrap();
+        assert_eq!((chord.root, chord.quality), (root, quality), "{chroma:?}");
+        assert!((chord.confidence - confidence).abs() < 1e-6, "{chord:?}");
+    }
+}
+
+#[test]
+fn test_detect_chord_transposed_reports_original_root() {
+    let mut a_minor = chroma_of(&[9, 0, 4]);
+    a_minor[2] = 0.1;
+    let mut detector = ChordDetector::builder().detect_bass(true).build();
+    let plain = detector.detect_chord(&a_minor).unwrap();
+    for semitones in [-14, -3, 0, 5, 11, 30] {
+        assert_eq!(detector.detect_chord_transposed(&a_minor, semitones).unwrap(), plain);
+    }
+
+    assert_eq!(NoteName::A.transpose(3), NoteName::C);
+    assert_eq!(NoteName::C.transpose(-13), NoteName::B);
+    assert_eq!(NoteName::Unknown.transpose(4), NoteName::Unknown);
+}
+
+#[test]
+fn test_detect_with_margin() {
+    // Without a missing-note penalty a bare triad ties with every seventh
+    // containing it, so demand complete voicings
+    let mut detector = ChordDetector::builder()
+        .missing_note_tolerance(0)
+        .normalized_confidence(true)
+        .build();
+    let mut c_major = chroma_of(&[0, 4, 7]);
+    c_major[4] = 0.8;
+    let (best, runner_up, margin) = detector.detect_with_margin(&c_major).unwrap();
+    assert_eq!((best.root, best.quality), (NoteName::C, ChordKind::Major));
+    assert_ne!((runner_up.root, runner_up.quality), (best.root, best.quality));
+    assert!((margin - (best.confidence - runner_up.confidence)).abs() < 1e-6);
+    assert!(margin > 0.1, "clean margin {margin}");
+
+    // C and E alone are shared by C major and A minor
+    let (best, runner_up, ambiguous) = detector.detect_with_margin(&chroma_of(&[0, 4])).unwrap();
+    let roots = [best.root, runner_up.root];
+    assert!(roots.contains(&NoteName::C) && roots.contains(&NoteName::A), "{roots:?}");
+    assert!(ambiguous < 0.01, "ambiguous margin {ambiguous}");
+
+    let (silent, _, zero) = detector.detect_with_margin(&[0.0; 12]).unwrap();
+    assert_eq!((silent.root, zero), (NoteName::Unknown, 0.0));
+}
+
+#[test]
+fn test_detect_chord_full_peakedness() {
+    let mut detector = ChordDetector::new();
+    let (chord, peaked) = detector.detect_chord_full(&chroma_of(&[0, 4, 7])).unwrap();
+    assert_eq!((chord.root, chord.quality), (NoteName::C, ChordKind::Major));
+    assert!(peaked > 0.9, "clean ratio {peaked}");
+
+    // the ratio ignores loudness
+    let quiet: Vec<f32> = chroma_of(&[0, 4, 7]).iter().map(|c| c * 0.1).collect();
+    let (_, quiet_ratio) = detector.detect_chord_full(&quiet).unwrap();
+    assert!((quiet_ratio - peaked).abs() < 1e-4, "{quiet_ratio} vs {peaked}");
+
+    let flat: Vec<f32> = (0..12).map(|i| 0.8 + 0.02 * (i % 3) as f32).collect();
+    let (_, ambiguous) = detector.detect_chord_full(&flat).unwrap();
+    assert!(ambiguous < 0.2, "flat ratio {ambiguous}");
+    assert!(ambiguous < peaked);
+
+    // the ratio is on the same scale whether or not scores are rooted
+    let mut exact = ChordDetector::builder().exact_confidence(true).build();
+    let mut muddy = chroma_of(&[0, 4, 7]);
+    muddy[1] = 0.4;
+    for chroma in [&muddy[..], &flat[..]] {
+        let (_, rooted) = exact.detect_chord_full(chroma).unwrap();
+        let (_, squared) = detector.detect_chord_full(chroma).unwrap();
+        assert!((rooted - squared).abs() < 1e-5, "{rooted} vs {squared}");
+    }
+
+    let (silent, zero) = detector.detect_chord_full(&[0.0; 12]).unwrap();
+    assert_eq!((silent.root, zero), (NoteName::Unknown, 0.0));
+}
+
+#[test]
+// The D in this chroma reads as a ninth once `extended_chords` is enabled
+#[cfg(not(feature = "extended_chords"))]
+fn test_tone_fit_reports_missing_fifth() {
+    // C and E with no G, plus a stray D
+    let mut chroma = chroma_of(&[0, 4]);
+    chroma[2] = 0.3;
+
+    let mut detector = ChordDetector::new();
+    let ToneFit {
+        chord,
+        tones,
+        extra,
+    } = detector.tone_fit(&chroma).unwrap();
+    assert_eq!((chord.root, chord.quality), (NoteName::C, ChordKind::Major));
+
+    let notes: Vec<NoteName> = tones.iter().map(|&(note, _)| note).collect();
+    assert_eq!(notes, chord.pitch_classes());
+    assert!(tones[0].1 > 0.5 && tones[1].1 > 0.5, "{tones:?}");
+    assert!(tones[2].1 < 0.05, "fifth should be missing: {tones:?}");
+
+    let (note, energy) = extra.unwrap();
+    assert_eq!(note, NoteName::D);
+    assert!(energy > 0.0);
+
+    let silent = detector.tone_fit(&[0.0; 12]).unwrap();
+    assert!(silent.tones.is_empty() && silent.extra.is_none());
+}
+
+#[test]
+#[cfg(feature = "extended_chords")]
+fn test_tone_fit_reports_missing_fifth_extended() {
+    // A stray C# belongs to no extended C chord either
+    let mut chroma = chroma_of(&[0, 4]);
+    chroma[1] = 0.3;
+
+    let ToneFit {
+        chord,
+        tones,
+        extra,
+    } = ChordDetector::new().tone_fit(&chroma).unwrap();
+    assert_eq!((chord.root, chord.quality), (NoteName::C, ChordKind::Major));
+    assert!(tones[2].1 < 0.05, "fifth should be missing: {tones:?}");
+    assert_eq!(extra.unwrap().0, NoteName::Cs);
+}
+
+#[test]
+fn test_min_confidence_gates_muddy_chords() {
+    let mut detector = ChordDetector::builder().min_confidence(0.8).build();
+
+    let chord = detector.detect_chord_gated(&chroma_of(&[0, 4, 7])).unwrap();
+    let chord = chord.expect("a clean triad passes the gate");
+    assert_eq!((chord.root, chord.quality), (NoteName::C, ChordKind::Major));
+
+    // A cluster of five adjacent semitones matches no template well
+    let muddy = chroma_of(&[0, 1, 2, 3, 4]);
+    assert_eq!(detector.detect_chord_gated(&muddy).unwrap(), None);
+    assert_ne!(detector.detect_chord(&muddy).unwrap().quality, ChordKind::Unknown);
+
+    // Silence is always suppressed
+    assert_eq!(detector.detect_chord_gated(&[0.0; 12]).unwrap(), None);
+    assert!(ChordDetector::new().detect_chord_gated(&muddy).unwrap().is_some());
+    assert!(ChordDetector::builder().min_confidence(1.5).try_build().is_err());
+}
+
+#[test]
+fn test_weighted_smoothing_steadies_alternating_frames() {
+    // Clean C major alternating with frames where a stray minor third
+    // outweighs the major one and the per-frame winner flips to C minor
+    let clean = chroma_of(&[0, 4, 7]);
+    let mut noisy = chroma_of(&[0, 3, 7]);
+    noisy[3] = 0.9;
+    noisy[4] = 0.5;
+    let frames: Vec<[f32; 12]> = (0..12).map(|i| if i % 2 == 0 { clean } else { noisy }).collect();
+
+    let label = |chord: Chord| (chord.root, chord.quality);
+    let mut raw = ChordDetector::new();
+    let per_frame: Vec<_> = frames.iter().map(|f| label(raw.detect_chord(f).unwrap())).collect();
+    assert_eq!(per_frame[0], (NoteName::C, ChordKind::Major));
+    assert_eq!(per_frame[1], (NoteName::C, ChordKind::Minor));
+
+    let mut smoothed = ChordDetector::builder().smoothing_alpha(0.3).build();
+    let weighted: Vec<_> = frames
+        .iter()
+        .map(|f| label(smoothed.detect_chord_weighted(f).unwrap()))
+        .collect();
+    let changes =
+        |labels: &[(NoteName, ChordKind)]| labels.windows(2).filter(|w| w[0] != w[1]).count();
+    assert_eq!(changes(&per_frame), frames.len() - 1);
+    assert_eq!(changes(&weighted), 0, "{weighted:?}");
+    assert_eq!(weighted[0], (NoteName::C, ChordKind::Major));
+
+    // Alpha 1.0 follows every frame
+    let mut unsmoothed = ChordDetector::new();
+    for (frame, expected) in frames.iter().zip(&per_frame) {
+        assert_eq!(label(unsmoothed.detect_chord_weighted(frame).unwrap()), *expected);
+    }
+    assert!(ChordDetector::builder().smoothing_alpha(0.0).try_build().is_err());
+}
+
+#[test]
+fn test_allow_rootless_keeps_rootless_seventh_in_top_two() {
+    // Cmaj7 voiced without its root
+    let e_g_b = chroma_of(&[4, 7, 11]);
+    let cmaj7 = (NoteName::C, ChordKind::MajorSeventh);
+    let top_two = |detector: &mut ChordDetector| -> Vec<_> {
+        let top = detector.top_k(&e_g_b, 2).unwrap();
+        top.iter().map(|c| (c.root, c.quality)).collect()
+    };
+
+    let mut strict = ChordDetector::builder().missing_note_tolerance(0).build();
+    assert!(!top_two(&mut strict).contains(&cmaj7));
+
+    let mut rootless =
+        ChordDetector::builder().missing_note_tolerance(0).allow_rootless(true).build();
+    let top = top_two(&mut rootless);
+    assert_eq!(top[0], (NoteName::E, ChordKind::Minor));
+    assert!(top.contains(&cmaj7), "{top:?}");
+
+    // A missing third is still penalised, which the default detector
+    // does not do
+    let c_g_b = chroma_of(&[0, 7, 11]);
+    let chord = rootless.detect_chord(&c_g_b).unwrap();
+    assert_ne!((chord.root, chord.quality), cmaj7);
+    let chord = ChordDetector::new().detect_chord(&c_g_b).unwrap();
+    assert_eq!((chord.root, chord.quality), cmaj7);
+
+    // On its own, allow_rootless demands every tone but the root
+    let mut implied = ChordDetector::builder().allow_rootless(true).build();
+    assert_eq!(top_two(&mut implied), top_two(&mut rootless));
+    let chord = implied.detect_chord(&c_g_b).unwrap();
+    assert_ne!((chord.root, chord.quality), cmaj7);
+}
+
+#[test]
+fn test_chord_kind_families() {
+    assert_eq!(ChordKind::PowerFifth.num_tones(), 2);
+    assert!(ChordKind::PowerFifth.is_dyad());
+    assert!(ChordKind::Major.is_triad());
+    assert!(ChordKind::SuspendedFourth.is_triad());
+    assert!(ChordKind::DominantSeventh.is_seventh());
+    assert!(!ChordKind::DominantSeventh.is_triad());
+    assert_eq!(ChordKind::Unknown.num_tones(), 0);
+    assert!(!ChordKind::Custom(0).is_triad());
+
+    // Every built-in variant appears once, in at most one family
+    let mut seen = Vec::new();
+    for kind in ChordKind::ALL {
+        let families = [kind.is_dyad(), kind.is_triad(), kind.is_seventh()];
+        let family_count = families.iter().filter(|&&f| f).count();
+        match kind {
+            ChordKind::Major
+            | ChordKind::Minor
+            | ChordKind::PowerFifth
+            | ChordKind::DominantSeventh
+            | ChordKind::MajorSeventh
+            | ChordKind::MinorSeventh
+            | ChordKind::Diminished
+            | ChordKind::Augmented
+            | ChordKind::SuspendedSecond
+            | ChordKind::SuspendedFourth
+            | ChordKind::HalfDiminished
+            | ChordKind::DiminishedSeventh => assert_eq!(family_count, 1, "{kind:?}"),
+            #[cfg(feature = "extended_chords")]
+            ChordKind::Major6 | ChordKind::Minor6 | ChordKind::Add9 | ChordKind::DominantNinth => {
+                assert_eq!(family_count, 0, "{kind:?}")
+            }
+            ChordKind::Unknown | ChordKind::Custom(_) => panic!("{kind:?} is not built in"),
+        }
+        assert!(!seen.contains(&kind));
+        seen.push(kind);
+    }
+    let expected = if cfg!(feature = "extended_chords") { 16 } else { 12 };
+    assert_eq!(seen.len(), expected);
+}
+
+#[test]
+fn test_weight_bass_prefers_loudest_root() {
+    // G-C-D with a doubled G: Gsus4 and Csus2 share these pitch classes
+    let mut chroma = [0.0; 12];
+    for (bin, energy) in [(7, 1.0), (0, 0.6), (2, 0.6)] {
+        chroma[bin] = energy;
+    }
+    let csus2 = Chord {
+        root: NoteName::C,
+        quality: ChordKind::SuspendedSecond,
+        confidence: 0.0,
+        bass: None,
+    };
+
+    // Unweighted the two readings tie and the earlier quality wins
+    let mut plain = ChordDetector::new();
+    let chord = plain.detect_chord(&chroma).unwrap();
+    assert_eq!((chord.root, chord.quality), (NoteName::C, ChordKind::SuspendedSecond));
+    let (_, plain_csus2) = plain.is_chord(&chroma, &csus2).unwrap();
+
+    let mut weighted = ChordDetector::builder().weight_bass(0.5).build();
+    let chord = weighted.detect_chord(&chroma).unwrap();
+    assert_eq!((chord.root, chord.quality), (NoteName::G, ChordKind::SuspendedFourth));
+    let (is_top, weighted_csus2) = weighted.is_chord(&chroma, &csus2).unwrap();
+    assert!(!is_top);
+    assert!(weighted_csus2 > plain_csus2);
+
+    // A chroma whose root is already loudest is scored as before
+    let c_major = [1.0, 0.0, 0.0, 0.0, 0.6, 0.0, 0.0, 0.6, 0.0, 0.0, 0.0, 0.0];
+    assert_eq!(
+        weighted.detect_chord(&c_major).unwrap(),
+        plain.detect_chord(&c_major).unwrap()
+    );
+    assert!(ChordDetector::builder().weight_bass(-0.1).try_build().is_err());
+}
+
+#[test]
+#[cfg(feature = "extended_chords")]
+fn test_extended_chords_beat_plain_triads() {
+    let label = |chord: Chord| (chord.root, chord.quality);
+    let mut detector = ChordDetector::new();
+
+    // Cadd9: no triad or seventh holds the D
+    let chord = detector.detect_chord(&chroma_of(&[0, 2, 4, 7])).unwrap();
+    assert_eq!(label(chord), (NoteName::C, ChordKind::Add9));
+    assert_eq!(chord.name(), "Cadd9");
+
+    // C6 and Cm6 tie exactly with Am7 and Am7b5; the sixth reading wins
+    let c6 = chroma_of(&[0, 4, 7, 9]);
+    assert_eq!(label(detector.detect_chord(&c6).unwrap()), (NoteName::C, ChordKind::Major6));
+    let chord = detector.detect_chord(&chroma_of(&[0, 3, 7, 9])).unwrap();
+    assert_eq!(label(chord), (NoteName::C, ChordKind::Minor6));
+    let chord = detector.detect_chord(&chroma_of(&[0, 2, 4, 7, 10])).unwrap();
+    assert_eq!(label(chord), (NoteName::C, ChordKind::DominantNinth));
+
+    // A louder A tips the same pitch classes to Am7 under weight_bass
+    let mut loud_a = c6;
+    for bin in [0, 4, 7] {
+        loud_a[bin] = 0.7;
+    }
+    let mut weighted = ChordDetector::builder().weight_bass(0.5).build();
+    let chord = weighted.detect_chord(&loud_a).unwrap();
+    assert_eq!(label(chord), (NoteName::A, ChordKind::MinorSeventh));
+
+    for kind in [ChordKind::Major6, ChordKind::Minor6, ChordKind::Add9, ChordKind::DominantNinth] {
+        assert_eq!(ChordKind::try_from(kind.shorthand()).unwrap(), kind);
+    }
+}
+
+#[test]
+#[cfg(feature = "extended_chords")]
+fn test_inversion_with_ninth_in_bass() {
+    let over = |quality, bass| Chord {
+        root: NoteName::C,
+        quality,
+        confidence: 0.0,
+        bass: Some(bass),
+    };
+    assert_eq!(over(ChordKind::Add9, NoteName::D).inversion(), Some(3));
+    assert_eq!(over(ChordKind::Add9, NoteName::D).to_string(), "Cadd9/D");
+    assert_eq!(over(ChordKind::DominantNinth, NoteName::D).inversion(), Some(4));
+    assert_eq!(over(ChordKind::Major6, NoteName::A).inversion(), Some(3));
+}
@@ -0,0 +1,1051 @@
+//! Tests for chromagram computation and helpers.
+#![cfg(feature = "chromagram")]
+
+use chord_detector::chromagram::{active_notes, fuse_stem_chroma, mel_to_chroma, transpose_chroma};
+use chord_detector::{
+    ChordDetector, ChordKind, Chromagram, ChromagramError, DecimationQuality, NormKind, NoteName,
+    TransformKind, WindowKind,
+};
+use std::f32::consts::PI;
+
+const SAMPLE_RATE: usize = 44_100;
+const FRAME: usize = 1024;
+
+/// Generate `len` samples of a sine at `freq` Hz.
+fn sine(freq: f32, len: usize) -> Vec<f32> {
+    (0..len)
+        .map(|i| (2.0 * PI * freq * i as f32 / SAMPLE_RATE as f32).sin())
+        .collect()
+}
+
+/// Add deterministic white noise of the given amplitude to `samples`.
+fn add_noise(samples: &mut [f32], amplitude: f32) {
+    let mut state = 0x2545_f491_u32;
+    for s in samples {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        *s += amplitude * (state as f32 / u32::MAX as f32 * 2.0 - 1.0);
+    }
+}
+
+/// Push `samples` through `chroma` frame by frame, collecting every emission.
+fn run(chroma: &mut Chromagram, samples: &[f32]) -> Vec<[f32; 12]> {
+    samples
+        .chunks_exact(FRAME)
+        .filter_map(|frame| chroma.next(frame).unwrap())
+        .collect()
+}
+
+#[test]
+fn test_active_notes_relative_threshold() {
+    let mut chroma = [0.0; 12];
+    chroma[0] = 1.0; // C
+    chroma[4] = 0.8; // E
+    chroma[7] = 0.6; // G
+    chroma[10] = 0.1; // A#
+
+    assert_eq!(
+        active_notes(&chroma, 0.5),
+        vec![NoteName::C, NoteName::E, NoteName::G]
+    );
+    assert_eq!(active_notes(&chroma, 0.0).len(), 4);
+    assert!(active_notes(&[0.0; 12], 0.5).is_empty());
+}
+
+#[test]
+fn test_window_unwraps_oldest_sample_first() {
+    // A steady sine is continuous across the analysis window only when the
+    // ring buffer is unwrapped from its oldest sample; starting anywhere
+    // else splices two ends of the signal together and smears the peak
+    for freq in [261.63, 445.0] {
+        let mut chroma = Chromagram::builder().build().unwrap();
+        let out = run(&mut chroma, &sine(freq, 2 * SAMPLE_RATE));
+        let last = out.last().unwrap();
+        let peak = last.iter().cloned().fold(0.0, f32::max);
+        let share = peak / last.iter().sum::<f32>();
+        assert!(share > 0.9, "{freq} Hz: peak holds {share} of the chroma");
+    }
+}
+
+#[test]
+fn test_note_carryover_bridges_window_edge() {
+    // One second of A3 followed by one second of silence
+    let mut samples = sine(220.0, SAMPLE_RATE);
+    samples.extend(vec![0.0; SAMPLE_RATE]);
+
+    let mut plain = Chromagram::builder().build().unwrap();
+    let mut held = Chromagram::builder().note_carryover(0.5).build().unwrap();
+    let plain_out = run(&mut plain, &samples);
+    let held_out = run(&mut held, &samples);
+
+    let last = plain_out.len() - 1;
+    let peak = held_out.iter().map(|c| c[9]).fold(0.0, f32::max);
+    assert_eq!(plain_out[last][9], 0.0);
+    assert!(held_out[last][9] > 0.0 && held_out[last][9] < peak);
+    assert!(held_out.iter().zip(&plain_out).all(|(h, p)| h[9] >= p[9]));
+}
+
+#[test]
+fn test_resolve_harmonic_collisions_splits_shared_peak() {
+    // A lone G4: C3's third harmonic lands on the same peak as G4
+    let samples = sine(392.0, SAMPLE_RATE);
+    let builder = || Chromagram::builder().num_harmonics(3);
+
+    let shared = run(&mut builder().build().unwrap(), &samples);
+    let split = run(
+        &mut builder().resolve_harmonic_collisions(true).build().unwrap(),
+        &samples,
+    );
+
+    let (shared, split) = (shared.last().unwrap(), split.last().unwrap());
+    assert!(split[0] < shared[0]);
+    assert!(split.iter().sum::<f32>() < shared.iter().sum::<f32>());
+    assert_eq!(active_notes(split, 1.0), vec![NoteName::G]);
+}
+
+#[test]
+fn test_octave_weights_favor_the_fundamental() {
+    // C3 with an equally loud third harmonic, G4, in the second octave
+    let samples: Vec<f32> = sine(130.81, SAMPLE_RATE)
+        .iter()
+        .zip(sine(392.0, SAMPLE_RATE))
+        .map(|(c, g)| c + g)
+        .collect();
+
+    let mut flat = Chromagram::builder().build().unwrap();
+    let flat = *run(&mut flat, &samples).last().unwrap();
+    let mut bass = Chromagram::builder().octave_weights(vec![10.0, 1.0]).build().unwrap();
+    let bass = *run(&mut bass, &samples).last().unwrap();
+
+    assert!(flat[7] > flat[0]);
+    assert!(bass[0] > bass[7]);
+    assert!(bass[0] / bass[7] > flat[0] / flat[7]);
+
+    for weights in [vec![1.0], vec![1.0, -1.0], vec![1.0, f32::NAN]] {
+        assert!(matches!(
+            Chromagram::builder().octave_weights(weights).build(),
+            Err(ChromagramError::Configuration(_))
+        ));
+    }
+}
+
+#[test]
+fn test_octave_energies_sum_to_chroma() {
+    // A3 sits in the first analyzed octave (C3..B3)
+    let mut chromagram = Chromagram::builder().build().unwrap();
+    let low = run(&mut chromagram, &sine(220.0, SAMPLE_RATE));
+    let octaves = chromagram.octave_energies();
+    assert_eq!(octaves.len(), 2);
+    assert!(octaves[0][9] > octaves[1][9]);
+
+    let last = low.last().unwrap();
+    for n in 0..12 {
+        assert!((octaves[0][n] + octaves[1][n] - last[n]).abs() < 1e-3);
+    }
+}
+
+#[test]
+fn test_next_unchecked_matches_next() {
+    let samples = sine(261.63, SAMPLE_RATE);
+    let mut checked = Chromagram::builder().build().unwrap();
+    let mut fast = Chromagram::builder().unchecked(true).build().unwrap();
+
+    let expected = run(&mut checked, &samples);
+    let actual: Vec<_> = samples
+        .chunks_exact(FRAME)
+        .filter_map(|frame| fast.next_unchecked(frame))
+        .collect();
+    assert_eq!(actual, expected);
+}
+
+#[test]
+#[should_panic(expected = "invalid frame size")]
+fn test_next_unchecked_validates_by_default() {
+    let mut chromagram = Chromagram::builder().build().unwrap();
+    chromagram.next_unchecked(&[0.0; FRAME - 1]);
+}
+
+#[test]
+fn test_flush_analyzes_the_tail() {
+    // Silence up to an emission boundary, then a short E4 that never completes a hop
+    let mut samples = vec![0.0; 16 * FRAME];
+    samples.extend(sine(329.63, 2 * FRAME));
+
+    let mut chromagram = Chromagram::builder().build().unwrap();
+    let emitted = run(&mut chromagram, &samples);
+    assert!(emitted.iter().all(|c| c.iter().all(|&e| e == 0.0)));
+
+    let tail = chromagram.flush().unwrap();
+    assert_eq!(active_notes(&tail, 1.0), vec![NoteName::E]);
+    assert_eq!(chromagram.flush(), None);
+}
+
+#[test]
+fn test_mel_to_chroma_folds_band_centers() {
+    // Bands at 0 Hz, A4, A3, a slightly flat C#5 and an ultrasonic band
+    let freqs = [0.0, 440.0, 220.0, 550.0, 12_000.0];
+    let mel = [5.0, 1.0, 0.5, 0.25, 9.0];
+
+    let chroma = mel_to_chroma(&mel, &freqs);
+    assert_eq!(chroma[9], 1.5);
+    assert_eq!(chroma[1], 0.25);
+    assert_eq!(chroma.iter().sum::<f32>(), 1.75);
+}
+
+#[test]
+fn test_smoothing_alpha_reduces_variance() {
+    let mut samples = sine(261.63, 4 * SAMPLE_RATE);
+    add_noise(&mut samples, 2.0);
+
+    let flicker = |out: &[[f32; 12]]| -> f32 {
+        out.windows(2)
+            .map(|w| w[0].iter().zip(&w[1]).map(|(a, b)| (a - b).abs()).sum::<f32>())
+            .sum()
+    };
+
+    let plain = run(&mut Chromagram::builder().build().unwrap(), &samples);
+    let smooth = run(
+        &mut Chromagram::builder().smoothing_alpha(0.3).build().unwrap(),
+        &samples,
+    );
+    assert_eq!(plain[0], smooth[0]);
+    assert!(flicker(&smooth) < 0.75 * flicker(&plain));
+    assert!(Chromagram::builder().smoothing_alpha(0.0).build().is_err());
+}
+
+#[test]
+fn test_harmonic_rolloff_weights_upper_harmonics() {
+    // C3 with four decaying harmonics
+    let mut samples = vec![0.0; SAMPLE_RATE];
+    for h in 1..=4 {
+        for (s, t) in samples.iter_mut().zip(sine(130.81 * h as f32, SAMPLE_RATE)) {
+            *s += t / h as f32;
+        }
+    }
+
+    let c_bin = |rolloff: f32| {
+        let mut chromagram = Chromagram::builder().harmonic_rolloff(rolloff).build().unwrap();
+        run(&mut chromagram, &samples).last().unwrap()[0]
+    };
+    let default = run(&mut Chromagram::builder().build().unwrap(), &samples);
+
+    assert_eq!(c_bin(1.0), default.last().unwrap()[0]);
+    assert!(c_bin(0.0) > c_bin(1.0));
+    assert!(c_bin(1.0) > c_bin(2.0));
+    assert!(Chromagram::builder().harmonic_rolloff(-1.0).build().is_err());
+}
+
+#[test]
+fn test_current_tuning_cents_tracks_detuned_input() {
+    // A3 tuned 20 cents sharp
+    let freq = 220.0 * 2f32.powf(20.0 / 1200.0);
+    let mut chromagram = Chromagram::builder().build().unwrap();
+    assert_eq!(chromagram.current_tuning_cents(), 0.0);
+
+    run(&mut chromagram, &sine(freq, 3 * SAMPLE_RATE));
+    let cents = chromagram.current_tuning_cents();
+    assert!((cents - 20.0).abs() < 5.0, "estimated {cents} cents");
+}
+
+#[test]
+fn test_buffer_size_validation_and_resolution() {
+    assert!(Chromagram::builder().buffer_size(3000).build().is_err());
+    assert!(Chromagram::builder()
+        .buffer_size(2)
+        .downsample_factor(4)
+        .build()
+        .is_err());
+
+    // A smaller buffer emits more often and still finds the note
+    let samples = sine(220.0, SAMPLE_RATE);
+    let default = run(&mut Chromagram::builder().build().unwrap(), &samples);
+    let small = run(
+        &mut Chromagram::builder().buffer_size(4096).build().unwrap(),
+        &samples,
+    );
+    assert!(small.len() > default.len());
+    assert_eq!(active_notes(small.last().unwrap(), 1.0), vec![NoteName::A]);
+}
+
+#[test]
+fn test_tuning_hz_moves_reference_pitch() {
+    // A4 in a 432 Hz tuning sits ~32 cents flat of the 440 Hz grid, outside
+    // the peak search window, so only leakage reaches the A bin there
+    let mut samples = sine(432.0, SAMPLE_RATE * 2);
+    add_noise(&mut samples, 0.3);
+
+    let a_bin = |tuning: f32| {
+        let mut chroma = Chromagram::builder().tuning_hz(tuning).build().unwrap();
+        let last = *run(&mut chroma, &samples).last().unwrap();
+        let strongest = (0..12).max_by(|&a, &b| last[a].total_cmp(&last[b])).unwrap();
+        assert_eq!(strongest, 9);
+        last[9]
+    };
+
+    let tuned = a_bin(432.0);
+    let untuned = a_bin(440.0);
+    assert!(tuned > 2.0 * untuned, "tuned {tuned}, untuned {untuned}");
+
+    for bad in [0.0, -440.0, 399.0, 481.0, f32::NAN] {
+        assert!(Chromagram::builder().tuning_hz(bad).build().is_err());
+    }
+}
+
+#[test]
+fn test_reset_matches_fresh_instance() {
+    let build = || {
+        Chromagram::builder()
+            .note_carryover(0.5)
+            .smoothing_alpha(0.5)
+            .build()
+            .unwrap()
+    };
+    let first = sine(330.0, SAMPLE_RATE);
+    let second = sine(220.0, SAMPLE_RATE);
+
+    let mut reused = build();
+    run(&mut reused, &first);
+    reused.next(&first[..FRAME]).unwrap();
+    reused.reset();
+    assert_eq!(reused.flush(), None);
+    assert_eq!(reused.current_tuning_cents(), 0.0);
+
+    let mut fresh = build();
+    assert_eq!(run(&mut reused, &second), run(&mut fresh, &second));
+    assert_eq!(reused.octave_energies(), fresh.octave_energies());
+}
+
+#[test]
+fn test_equal_loudness_attenuates_low_notes() {
+    let samples: Vec<f32> = sine(130.81, SAMPLE_RATE)
+        .iter()
+        .zip(sine(392.0, SAMPLE_RATE))
+        .map(|(c, g)| c + g)
+        .collect();
+
+    let g_over_c = |equal_loudness: bool| {
+        let mut chroma = Chromagram::builder()
+            .equal_loudness(equal_loudness)
+            .build()
+            .unwrap();
+        let last = *run(&mut chroma, &samples).last().unwrap();
+        last[7] / last[0]
+    };
+
+    let flat = g_over_c(false);
+    let weighted = g_over_c(true);
+    assert!(weighted > 2.0 * flat, "flat {flat}, weighted {weighted}");
+}
+
+#[test]
+fn test_window_kind_coefficients() {
+    let cases = [
+        (WindowKind::Hamming, 0.08),
+        (WindowKind::Hann, 0.0),
+        (WindowKind::Blackman, 0.0),
+        (WindowKind::BlackmanHarris, 0.00006),
+    ];
+    for (kind, first) in cases {
+        let window = kind.coefficients(9);
+        assert!((window[0] - first).abs() < 1e-5, "{kind:?} first {}", window[0]);
+        assert!((window[8] - first).abs() < 1e-5, "{kind:?} last {}", window[8]);
+        assert!((window[4] - 1.0).abs() < 1e-5, "{kind:?} middle {}", window[4]);
+    }
+
+    let mut chroma = Chromagram::builder()
+        .window(WindowKind::BlackmanHarris)
+        .build()
+        .unwrap();
+    let last = *run(&mut chroma, &sine(220.0, SAMPLE_RATE)).last().unwrap();
+    let strongest = (0..12).max_by(|&a, &b| last[a].total_cmp(&last[b])).unwrap();
+    assert_eq!(strongest, 9);
+}
+
+#[test]
+fn test_frame_size_must_divide_by_downsample_factor() {
+    let err = Chromagram::builder().frame_size(1001).downsample_factor(4).build();
+    assert!(matches!(err, Err(ChromagramError::Configuration(_))));
+
+    let mut chroma = Chromagram::builder().frame_size(1000).downsample_factor(4).build().unwrap();
+    assert!(chroma.next(&[0.0; 1000]).is_ok());
+}
+
+#[cfg(feature = "sliding-dft")]
+#[test]
+fn test_incremental_spectrum_matches_fft() {
+    let mut samples: Vec<f32> = sine(261.63, SAMPLE_RATE * 2)
+        .iter()
+        .zip(sine(329.63, SAMPLE_RATE * 2))
+        .zip(sine(392.0, SAMPLE_RATE * 2))
+        .map(|((c, e), g)| c + e + g)
+        .collect();
+    add_noise(&mut samples, 0.1);
+
+    for window in [WindowKind::Hamming, WindowKind::BlackmanHarris] {
+        let mut full = Chromagram::builder().window(window).build().unwrap();
+        let mut incremental = Chromagram::builder()
+            .window(window)
+            .incremental_spectrum(true)
+            .build()
+            .unwrap();
+
+        let expected = run(&mut full, &samples);
+        let actual = run(&mut incremental, &samples);
+        assert_eq!(expected.len(), actual.len());
+        for (e, a) in expected.iter().zip(&actual) {
+            for (x, y) in e.iter().zip(a) {
+                assert!((x - y).abs() <= 0.01 * x.abs().max(1.0), "{window:?}: {x} vs {y}");
+            }
+        }
+    }
+}
+
+#[test]
+fn test_magnitude_spectrum_peak_frequency() {
+    let mut chroma = Chromagram::builder().build().unwrap();
+    assert!(chroma.magnitude_spectrum().iter().all(|&m| m == 0.0));
+    run(&mut chroma, &sine(440.0, SAMPLE_RATE));
+
+    let spectrum = chroma.magnitude_spectrum();
+    assert_eq!(spectrum.len(), 8192 / 2 + 1);
+    let peak = (0..spectrum.len())
+        .max_by(|&a, &b| spectrum[a].total_cmp(&spectrum[b]))
+        .unwrap();
+    let bin_width = chroma.bin_frequency(1);
+    assert!((chroma.bin_frequency(peak) - 440.0).abs() <= bin_width);
+    assert_eq!(chroma.bin_frequency(0), 0.0);
+    assert!((bin_width - 44_100.0 / 4.0 / 8192.0).abs() < 1e-6);
+}
+
+/// Hamming-windowed magnitude spectrum of `samples` from a full complex FFT.
+fn reference_spectrum(samples: &[f32]) -> Vec<f32> {
+    use rustfft::{num_complex::Complex, FftPlanner};
+
+    let mut buffer: Vec<Complex<f32>> = samples.iter().map(|&x| Complex::new(x, 0.0)).collect();
+    for (c, w) in buffer.iter_mut().zip(WindowKind::Hamming.coefficients(samples.len())) {
+        c.re *= w;
+    }
+    FftPlanner::<f32>::new().plan_fft_forward(samples.len()).process(&mut buffer);
+    buffer[..=samples.len() / 2].iter().map(|c| c.norm()).collect()
+}
+
+fn assert_spectra_match(actual: &[f32], expected: &[f32]) {
+    assert_eq!(actual.len(), expected.len());
+    let peak = actual.iter().cloned().fold(0.0, f32::max);
+    for (mag, reference) in actual.iter().zip(expected) {
+        assert!((mag - reference).abs() <= peak * 1e-4, "{mag} vs {reference}");
+    }
+}
+
+#[test]
+fn test_real_fft_matches_complex_reference() {
+    const N: usize = 8192;
+    let mut chroma = Chromagram::builder()
+        .downsample_factor(1)
+        .build()
+        .unwrap();
+    let input = sine(440.0, N);
+    run(&mut chroma, &input);
+    chroma.flush();
+
+    // At factor 1 the samples are analyzed unfiltered
+    assert_spectra_match(chroma.magnitude_spectrum(), &reference_spectrum(&input));
+}
+
+#[test]
+fn test_antialias_off_passes_samples_through() {
+    const N: usize = 8192;
+    let input = sine(440.0, 4 * N);
+
+    let mut chroma = Chromagram::builder()
+        .downsample_factor(1)
+        .antialias(false)
+        .build()
+        .unwrap();
+    run(&mut chroma, &input[..N]);
+    chroma.flush();
+    assert_spectra_match(chroma.magnitude_spectrum(), &reference_spectrum(&input[..N]));
+
+    // Still decimated, but every fourth sample is kept as is
+    let mut chroma = Chromagram::builder().antialias(false).build().unwrap();
+    run(&mut chroma, &input);
+    chroma.flush();
+    let decimated: Vec<f32> = input.iter().step_by(4).copied().collect();
+    assert_spectra_match(chroma.magnitude_spectrum(), &reference_spectrum(&decimated));
+}
+
+#[test]
+fn test_constant_q_isolates_low_bass_note() {
+    // Ratio of the C bin to the strongest other pitch class
+    let dominance = |transform| {
+        let mut chroma = Chromagram::builder()
+            .buffer_size(4096)
+            .transform(transform)
+            .build()
+            .unwrap();
+        let emissions = run(&mut chroma, &sine(130.81, SAMPLE_RATE));
+        let last = emissions.last().unwrap();
+        let rest = last[1..].iter().cloned().fold(0.0, f32::max);
+        last[0] / rest
+    };
+
+    let fft = dominance(TransformKind::Fft);
+    let constant_q = dominance(TransformKind::ConstantQ);
+    assert!(constant_q > 10.0 * fft, "constant-Q {constant_q} vs FFT {fft}");
+}
+
+#[test]
+fn test_small_hop_emits_more_often() {
+    let input = sine(440.0, SAMPLE_RATE);
+    let mut default = Chromagram::builder().build().unwrap();
+    let mut fine = Chromagram::builder().hop_size(1024).build().unwrap();
+    let coarse_count = run(&mut default, &input).len();
+    let fine_count = run(&mut fine, &input).len();
+    assert_eq!(fine_count, input.len() / FRAME);
+    assert!(fine_count > coarse_count);
+
+    // A hop shorter than the frame still emits once per frame
+    let mut tiny = Chromagram::builder().hop_size(256).build().unwrap();
+    assert_eq!(run(&mut tiny, &input).len(), input.len() / FRAME);
+
+    for hop in [0, 8193] {
+        let result = Chromagram::builder().hop_size(hop).build();
+        assert!(matches!(result, Err(ChromagramError::Configuration(_))));
+    }
+}
+
+#[test]
+fn test_next_all_reports_every_hop_in_an_oversized_frame() {
+    let mut chroma = Chromagram::builder()
+        .frame_size(16_384)
+        .hop_size(4096)
+        .build()
+        .unwrap();
+    let input = sine(440.0, 16_384);
+    let emitted = chroma.next_all(&input).unwrap();
+    assert_eq!(emitted.len(), 4);
+    assert!(emitted.windows(2).all(|pair| pair[0] != pair[1]));
+    let a = NoteName::A.to_semitone().unwrap();
+    let last = emitted.last().unwrap();
+    assert!(last.iter().all(|&bin| bin <= last[a]));
+
+    // `next` on the same input reports only the final analysis
+    let mut single = Chromagram::builder()
+        .frame_size(16_384)
+        .hop_size(4096)
+        .build()
+        .unwrap();
+    assert_eq!(single.next(&input).unwrap(), Some(*last));
+
+    assert!(matches!(
+        chroma.next_all(&input[..FRAME]),
+        Err(ChromagramError::InvalidFrameSize { expected: 16_384, got: FRAME })
+    ));
+}
+
+#[test]
+fn test_normalize_l2_gives_unit_norm() {
+    let mut chroma = Chromagram::builder().normalize(NormKind::L2).build().unwrap();
+    let mut input = sine(261.63, SAMPLE_RATE);
+    add_noise(&mut input, 0.05);
+    for emitted in run(&mut chroma, &input) {
+        let norm = emitted.iter().map(|c| c * c).sum::<f32>().sqrt();
+        assert!((norm - 1.0).abs() < 1e-5, "norm {norm}");
+    }
+
+    let mut max = Chromagram::builder().normalize(NormKind::Max).build().unwrap();
+    let last = *run(&mut max, &input).last().unwrap();
+    assert_eq!(last.iter().cloned().fold(0.0, f32::max), 1.0);
+}
+
+#[test]
+fn test_normalize_silence_stays_zero() {
+    for kind in [NormKind::L1, NormKind::L2, NormKind::Max] {
+        let mut chroma = Chromagram::builder().normalize(kind).build().unwrap();
+        let emitted = run(&mut chroma, &vec![0.0; SAMPLE_RATE]);
+        assert!(!emitted.is_empty());
+        assert!(emitted.iter().flatten().all(|&c| c == 0.0));
+    }
+}
+
+#[test]
+fn test_samples_until_next_counts_down_and_wraps() {
+    let mut chroma = Chromagram::builder().build().unwrap();
+    assert_eq!(chroma.latency_samples(), 8192 * 4);
+    assert_eq!(chroma.samples_until_next(), 4096);
+
+    let frame = sine(440.0, FRAME);
+    let mut previous = chroma.samples_until_next();
+    for _ in 0..3 {
+        assert_eq!(chroma.next(&frame).unwrap(), None);
+        let remaining = chroma.samples_until_next();
+        assert_eq!(remaining, previous - FRAME);
+        previous = remaining;
+    }
+    assert!(chroma.next(&frame).unwrap().is_some());
+    assert_eq!(chroma.samples_until_next(), 4096);
+}
+
+#[test]
+fn test_f64_samples_match_f32() {
+    let input = sine(261.63, SAMPLE_RATE);
+    let wide: Vec<f64> = input.iter().map(|&s| s as f64).collect();
+
+    let mut narrow_chroma = Chromagram::builder().build().unwrap();
+    let mut wide_chroma = Chromagram::builder().build_for::<f64>().unwrap();
+    let narrow = run(&mut narrow_chroma, &input);
+    let wide: Vec<_> = wide
+        .chunks_exact(FRAME)
+        .filter_map(|frame| wide_chroma.next(frame).unwrap())
+        .collect();
+
+    assert_eq!(narrow.len(), wide.len());
+    for (a, b) in narrow.iter().zip(&wide) {
+        let peak = a.iter().cloned().fold(0.0, f32::max);
+        for (x, y) in a.iter().zip(b) {
+            assert!((x - y).abs() <= peak * 1e-4, "{x} vs {y}");
+        }
+    }
+}
+
+#[test]
+fn test_fuse_stem_chroma_bass_sets_root() {
+    let mut harmony = [0.0; 12];
+    for pc in [0, 4, 7] {
+        harmony[pc] = 4.0; // C-E-G
+    }
+    let mut bass = [0.0; 12];
+    bass[9] = 0.2; // A
+
+    let fused = fuse_stem_chroma(&bass, &harmony);
+    assert_eq!((fused[0], fused[9]), (1.0, 0.5));
+
+    let mut detector = ChordDetector::new();
+    let alone = detector.detect_chord(&harmony).unwrap();
+    assert_eq!((alone.root, alone.quality), (NoteName::C, ChordKind::Major));
+    let with_bass = detector.detect_chord(&fused).unwrap();
+    #[cfg(not(feature = "extended_chords"))]
+    assert_eq!((with_bass.root, with_bass.quality), (NoteName::A, ChordKind::MinorSeventh));
+    // A-C-E-G is also C6, which wins the tie once `extended_chords` is enabled
+    #[cfg(feature = "extended_chords")]
+    assert_eq!((with_bass.root, with_bass.quality), (NoteName::C, ChordKind::Major6));
+
+    assert_eq!(fuse_stem_chroma(&[0.0; 12], &harmony)[0], 1.0);
+}
+
+#[test]
+fn test_onset_detected_once_at_chord_start() {
+    let silence = 2 * SAMPLE_RATE;
+    let mut samples = vec![0.0; silence];
+    let chord: Vec<f32> = (0..2 * SAMPLE_RATE)
+        .map(|i| {
+            let t = i as f32 / SAMPLE_RATE as f32;
+            [261.63f32, 329.63, 392.0].iter().map(|f| (2.0 * PI * f * t).sin()).sum::<f32>() / 3.0
+        })
+        .collect();
+    samples.extend(chord);
+
+    for hop in [4096, 1024] {
+        let mut chroma = Chromagram::builder().hop_size(hop).build().unwrap();
+        let mut onsets = Vec::new();
+        let mut pushed = 0;
+        for frame in samples.chunks_exact(FRAME) {
+            pushed += FRAME;
+            if chroma.next(frame).unwrap().is_some() && chroma.onset_detected() {
+                onsets.push(pushed);
+            }
+        }
+
+        // Exactly one onset, at the first emission that hears the chord
+        assert_eq!(onsets.len(), 1, "hop {hop}: {onsets:?}");
+        assert!(onsets[0] > silence && onsets[0] <= silence + hop, "hop {hop}: {onsets:?}");
+    }
+}
+
+#[test]
+fn test_chroma_from_magnitude_matches_next() {
+    let samples = sine(261.63, SAMPLE_RATE);
+    let mut full = Chromagram::builder().note_carryover(0.5).build().unwrap();
+    let mut folded = Chromagram::builder().note_carryover(0.5).build().unwrap();
+
+    let mut compared = 0;
+    for frame in samples.chunks_exact(FRAME) {
+        if let Some(expected) = full.next(frame).unwrap() {
+            let actual = folded.chroma_from_magnitude(full.magnitude_spectrum()).unwrap();
+            assert_eq!(actual, expected);
+            compared += 1;
+        }
+    }
+    assert!(compared > 0);
+
+    assert!(matches!(
+        folded.chroma_from_magnitude(&[0.0; 16]),
+        Err(ChromagramError::InvalidSpectrumSize { expected: 4097, got: 16 })
+    ));
+}
+
+#[test]
+fn test_clone_continues_independently() {
+    let samples = sine(329.63, SAMPLE_RATE);
+    let (first, second) = samples.split_at(samples.len() / 2);
+
+    let mut original = Chromagram::builder().smoothing_alpha(0.5).build().unwrap();
+    let mut fresh_copy = original.clone();
+    run(&mut original, first);
+    let mut mid_copy = original.clone();
+
+    // Same state, same output
+    assert_eq!(run(&mut mid_copy, second), run(&mut original, second));
+    // The early clone never saw the first half
+    assert_eq!(run(&mut fresh_copy, samples.as_slice()), {
+        let mut rebuilt = Chromagram::builder().smoothing_alpha(0.5).build().unwrap();
+        run(&mut rebuilt, &samples)
+    });
+}
+
+#[test]
+fn test_antialias_filter_is_continuous_across_frames() {
+    const N: usize = 8192;
+    let input = sine(440.0, 4 * N);
+    let mut chroma = Chromagram::builder().build().unwrap();
+    run(&mut chroma, &input);
+    chroma.flush();
+
+    // Reference: one Butterworth low-pass at the decimated Nyquist frequency
+    // over the whole signal, then every fourth sample
+    let k = (std::f64::consts::PI / 8.0).tan();
+    let norm = 1.0 / (1.0 + std::f64::consts::SQRT_2 * k + k * k);
+    let b = [k * k * norm, 2.0 * k * k * norm, k * k * norm];
+    let a = [2.0 * (k * k - 1.0) * norm, (1.0 - std::f64::consts::SQRT_2 * k + k * k) * norm];
+    let (mut x1, mut x2, mut y1, mut y2) = (0.0, 0.0, 0.0, 0.0);
+    let filtered: Vec<f32> = input
+        .iter()
+        .map(|&x0| {
+            let x0 = x0 as f64;
+            let y0 = b[0] * x0 + b[1] * x1 + b[2] * x2 - a[0] * y1 - a[1] * y2;
+            (x2, x1, y2, y1) = (x1, x0, y1, y0);
+            y0 as f32
+        })
+        .step_by(4)
+        .collect();
+
+    assert_spectra_match(chroma.magnitude_spectrum(), &reference_spectrum(&filtered));
+}
+
+#[test]
+fn test_next_interleaved_matches_mono() {
+    let mono = sine(440.0, 8 * FRAME);
+    let stereo: Vec<f32> = mono.iter().flat_map(|&s| [s, s]).collect();
+
+    let mut mono_chroma = Chromagram::builder().build().unwrap();
+    let mut stereo_chroma = Chromagram::builder().build().unwrap();
+    let expected = run(&mut mono_chroma, &mono);
+    let got: Vec<_> = stereo
+        .chunks_exact(2 * FRAME)
+        .filter_map(|frame| stereo_chroma.next_interleaved(frame, 2).unwrap())
+        .collect();
+    assert!(!expected.is_empty());
+    assert_eq!(got, expected);
+
+    assert!(matches!(
+        stereo_chroma.next_interleaved(&stereo[..FRAME], 2),
+        Err(ChromagramError::InvalidFrameSize { expected: 2048, got: FRAME })
+    ));
+    assert!(matches!(
+        stereo_chroma.next_interleaved(&[], 0),
+        Err(ChromagramError::InvalidChannels)
+    ));
+}
+
+#[test]
+fn test_peak_interpolation_separates_low_notes() {
+    // With a 2048-point buffer a semitone near C3 spans under two bins, so
+    // the C# and D search windows also reach the C3 peak between bins 24
+    // and 25
+    let samples = sine(130.81, SAMPLE_RATE);
+    let share_of_c = |interpolate: bool| {
+        let mut chromagram = Chromagram::builder()
+            .buffer_size(2048)
+            .peak_interpolation(interpolate)
+            .build()
+            .unwrap();
+        let chroma = *run(&mut chromagram, &samples).last().unwrap();
+        chroma[0] / chroma.iter().sum::<f32>()
+    };
+
+    let rounded = share_of_c(false);
+    let interpolated = share_of_c(true);
+    assert!(rounded < 0.5, "rounded share {rounded}");
+    assert!(interpolated > 0.9, "interpolated share {interpolated}");
+}
+
+#[test]
+fn test_last_frame_time_tracks_window_center() {
+    let mut chromagram = Chromagram::builder().build().unwrap();
+    assert_eq!(chromagram.last_frame_sample(), 0);
+
+    // The first emission's window still reaches back before the stream
+    let samples = sine(440.0, 40 * FRAME);
+    let mut frames = samples.chunks_exact(FRAME);
+    while chromagram.next(frames.next().unwrap()).unwrap().is_none() {}
+    assert_eq!(chromagram.last_frame_sample(), 0);
+    for frame in frames {
+        chromagram.next(frame).unwrap();
+    }
+
+    let pushed = 40 * FRAME;
+    let hop = 4096;
+    let center = (pushed - chromagram.latency_samples() / 2) as f64 / SAMPLE_RATE as f64;
+    let time = chromagram.last_frame_time();
+    assert!((time - center).abs() <= hop as f64 / SAMPLE_RATE as f64, "{time} vs {center}");
+    assert_eq!(
+        chromagram.last_frame_sample(),
+        (pushed - chromagram.latency_samples() / 2) as u64
+    );
+
+    chromagram.reset();
+    assert_eq!(chromagram.last_frame_time(), 0.0);
+}
+
+#[test]
+fn test_high_decimation_quality_suppresses_aliasing() {
+    // At factor 8 the decimated Nyquist is 2756 Hz, so 4 kHz folds to 1512 Hz
+    let peak = |quality, freq| {
+        let mut chromagram = Chromagram::builder()
+            .downsample_factor(8)
+            .decimation_quality(quality)
+            .build()
+            .unwrap();
+        run(&mut chromagram, &sine(freq, SAMPLE_RATE));
+        chromagram.magnitude_spectrum().iter().cloned().fold(0.0, f32::max)
+    };
+
+    let fast = peak(DecimationQuality::Fast, 4000.0);
+    let high = peak(DecimationQuality::High, 4000.0);
+    assert!(high < fast / 5.0, "fast {fast}, high {high}");
+
+    // Well inside the passband both filters leave the tone alone
+    let fast = peak(DecimationQuality::Fast, 440.0);
+    let high = peak(DecimationQuality::High, 440.0);
+    assert!((high / fast - 1.0).abs() < 0.05, "fast {fast}, high {high}");
+}
+
+#[test]
+fn test_transpose_chroma_moves_detected_root() {
+    let mut c_major = [0.0; 12];
+    for (bin, energy) in [(0, 1.0), (4, 0.8), (7, 0.9)] {
+        c_major[bin] = energy;
+    }
+
+    let up = transpose_chroma(&c_major, 2);
+    assert_eq!((up[2], up[6], up[9]), (1.0, 0.8, 0.9));
+    for semitones in [-10, 14, 26] {
+        assert_eq!(transpose_chroma(&c_major, semitones), up);
+    }
+    assert_eq!(transpose_chroma(&c_major, -12), c_major);
+
+    let chord = ChordDetector::new().detect_chord(&up).unwrap();
+    assert_eq!((chord.root, chord.quality), (NoteName::D, ChordKind::Major));
+}
+
+#[test]
+fn test_input_rate_resamples_to_analysis_rate() {
+    // A 48 kHz recording of A4 read as 44.1 kHz would sound near G4
+    let a4: Vec<f32> = (0..48_000)
+        .map(|i| (2.0 * PI * 440.0 * i as f32 / 48_000.0).sin())
+        .collect();
+    let strongest = |chroma: [f32; 12]| {
+        (0..12).max_by(|&a, &b| chroma[a].total_cmp(&chroma[b])).unwrap()
+    };
+
+    let mut resampled = Chromagram::builder().input_rate(48_000).build().unwrap();
+    let last = *run(&mut resampled, &a4).last().unwrap();
+    assert_eq!(strongest(last), 9);
+
+    let mut mismatched = Chromagram::builder().build().unwrap();
+    let last = *run(&mut mismatched, &a4).last().unwrap();
+    assert_ne!(strongest(last), 9);
+
+    // Matching rates leave the pipeline untouched
+    let samples = sine(440.0, SAMPLE_RATE);
+    let mut plain = Chromagram::builder().build().unwrap();
+    let mut same = Chromagram::builder().input_rate(SAMPLE_RATE).build().unwrap();
+    assert_eq!(run(&mut plain, &samples), run(&mut same, &samples));
+
+    assert!(Chromagram::builder().input_rate(0).build().is_err());
+}
+
+#[test]
+fn test_harmonic_emphasis_rejects_transient() {
+    // Four seconds of C major with a loud noise burst, like a drum hit,
+    // two seconds in
+    let mut samples: Vec<f32> = sine(261.63, 4 * SAMPLE_RATE)
+        .iter()
+        .zip(sine(329.63, 4 * SAMPLE_RATE))
+        .zip(sine(392.0, 4 * SAMPLE_RATE))
+        .map(|((c, e), g)| (c + e + g) / 3.0)
+        .collect();
+    add_noise(&mut samples[2 * SAMPLE_RATE..2 * SAMPLE_RATE + 2048], 40.0);
+
+    let chords = |emphasis| {
+        let mut chromagram = Chromagram::builder().harmonic_emphasis(emphasis).build().unwrap();
+        let mut detector = ChordDetector::builder().min_match_quality(0.7).build();
+        run(&mut chromagram, &samples)
+            .iter()
+            .map(|chroma| detector.detect_chord(chroma).unwrap())
+            .collect::<Vec<_>>()
+    };
+
+    let plain = chords(false);
+    assert!(plain.iter().any(|chord| chord.quality == ChordKind::Unknown));
+    for chord in chords(true) {
+        assert_eq!((chord.root, chord.quality), (NoteName::C, ChordKind::Major));
+    }
+}
+
+#[test]
+fn test_is_primed_after_a_full_window() {
+    let mut chromagram = Chromagram::builder().build().unwrap();
+    let frames_to_fill = chromagram.latency_samples() / FRAME;
+    let samples = sine(220.0, (frames_to_fill + 4) * FRAME);
+    let mut frames = samples.chunks_exact(FRAME);
+
+    // Chromagrams arrive before the buffer is full, flagged as unprimed
+    let mut early = 0;
+    for frame in frames.by_ref().take(frames_to_fill - 1) {
+        if chromagram.next(frame).unwrap().is_some() {
+            early += 1;
+        }
+        assert!(!chromagram.is_primed());
+    }
+    assert!(early > 0);
+
+    chromagram.next(frames.next().unwrap()).unwrap();
+    assert!(chromagram.is_primed());
+    for frame in frames {
+        chromagram.next(frame).unwrap();
+        assert!(chromagram.is_primed());
+    }
+
+    chromagram.reset();
+    assert!(!chromagram.is_primed());
+}
+
+#[test]
+fn test_next_multi_octave_separates_octaves() {
+    // A3 and A4 fall in the first and second analyzed octaves
+    let samples: Vec<f32> = sine(220.0, SAMPLE_RATE)
+        .iter()
+        .zip(sine(440.0, SAMPLE_RATE))
+        .map(|(low, high)| low + 0.5 * high)
+        .collect();
+    let mut chromagram = Chromagram::builder().num_harmonics(1).build().unwrap();
+    let mut folded = Chromagram::builder().num_harmonics(1).build().unwrap();
+
+    let mut last = None;
+    for frame in samples.chunks_exact(FRAME) {
+        let rows = chromagram.next_multi_octave(frame).unwrap();
+        let chroma = folded.next(frame).unwrap();
+        assert_eq!(rows.is_some(), chroma.is_some());
+        if let (Some(rows), Some(chroma)) = (rows, chroma) {
+            assert_eq!(rows.len(), 2);
+            for n in 0..12 {
+                assert!((rows[0][n] + rows[1][n] - chroma[n]).abs() < 1e-3);
+            }
+            last = Some(rows);
+        }
+    }
+
+    let rows = last.unwrap();
+    let strongest = |row: &[f32; 12]| (0..12).max_by(|&a, &b| row[a].total_cmp(&row[b])).unwrap();
+    assert_eq!((strongest(&rows[0]), strongest(&rows[1])), (9, 9));
+    assert!(rows[0][9] > 1.5 * rows[1][9], "{rows:?}");
+}
+
+#[test]
+fn test_validation_names_the_offending_field() {
+    let cases = [
+        ("frame_size", Chromagram::builder().frame_size(0)),
+        ("frame_size", Chromagram::builder().frame_size(1022)),
+        ("sampling_rate", Chromagram::builder().sampling_rate(0)),
+        ("input_rate", Chromagram::builder().input_rate(0)),
+        ("downsample_factor", Chromagram::builder().downsample_factor(0)),
+        ("downsample_factor", Chromagram::builder().downsample_factor(3)),
+        ("num_harmonics", Chromagram::builder().num_harmonics(0)),
+        ("num_octaves", Chromagram::builder().num_octaves(0)),
+        ("buffer_size", Chromagram::builder().buffer_size(3000)),
+        ("hop_size", Chromagram::builder().hop_size(0)),
+        ("hop_size", Chromagram::builder().harmonic_emphasis(true).hop_size(1)),
+        ("note_carryover", Chromagram::builder().note_carryover(1.0)),
+        ("harmonic_rolloff", Chromagram::builder().harmonic_rolloff(f32::NAN)),
+        ("smoothing_alpha", Chromagram::builder().smoothing_alpha(1.5)),
+        ("onset_threshold", Chromagram::builder().onset_threshold(0.0)),
+        ("tuning_hz", Chromagram::builder().tuning_hz(500.0)),
+        ("octave_weights", Chromagram::builder().octave_weights(vec![1.0])),
+        ("octave_weights", Chromagram::builder().octave_weights(vec![1.0, -1.0])),
+    ];
+    for (field, builder) in cases {
+        let validated = builder.validate();
+        let Err(ChromagramError::Configuration(msg)) = validated else {
+            panic!("{field}: expected a configuration error, got {validated:?}");
+        };
+        assert!(msg.contains(field), "{field}: {msg}");
+        assert!(builder.build().is_err());
+    }
+
+    assert!(Chromagram::builder().validate().is_ok());
+}
+
+#[test]
+fn test_push_is_independent_of_block_sizes() {
+    let samples = sine(261.63, SAMPLE_RATE);
+
+    let mut whole = Chromagram::builder().build().unwrap();
+    let expected = whole.push(&samples);
+    assert!(!expected.is_empty());
+
+    // Blocks of varying size, some shorter than a frame and some longer
+    let mut chunked = Chromagram::builder().build().unwrap();
+    let mut produced = Vec::new();
+    let mut rest = &samples[..];
+    for size in [1, 700, 3000, 5, 1024, 2047].iter().cycle() {
+        if rest.is_empty() {
+            break;
+        }
+        let (block, tail) = rest.split_at((*size).min(rest.len()));
+        produced.extend(chunked.push(block));
+        rest = tail;
+    }
+    assert_eq!(produced, expected);
+
+    // Whole frames give the same output as next_all
+    let mut framed = Chromagram::builder().build().unwrap();
+    let by_frame: Vec<[f32; 12]> = samples
+        .chunks_exact(FRAME)
+        .flat_map(|frame| framed.next_all(frame).unwrap())
+        .collect();
+    assert_eq!(by_frame, expected);
+}
+
+#[test]
+fn test_flush_analyzes_samples_held_back_by_push() {
+    // Silence up to an emission boundary, then a short C major in a block
+    // shorter than a frame
+    let frame_size = 4 * FRAME;
+    let chord: Vec<f32> = sine(261.63, frame_size - 96)
+        .iter()
+        .zip(sine(329.63, frame_size - 96))
+        .zip(sine(392.0, frame_size - 96))
+        .map(|((c, e), g)| c + e + g)
+        .collect();
+
+    let mut chromagram = Chromagram::builder().frame_size(frame_size).build().unwrap();
+    let emitted = chromagram.push(&vec![0.0; 8 * frame_size]);
+    assert!(emitted.iter().all(|c| c.iter().all(|&e| e == 0.0)));
+    assert!(chromagram.push(&chord).is_empty());
+
+    let tail = chromagram.flush().unwrap();
+    assert_eq!(active_notes(&tail, 0.5), vec![NoteName::C, NoteName::E, NoteName::G]);
+    assert_eq!(chromagram.flush(), None);
+}
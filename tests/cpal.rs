@@ -0,0 +1,22 @@
+//! Smoke test for the `cpal` input helper; skips when no device is present.
+#![cfg(feature = "cpal")]
+
+use chord_detector::{ChordDetector, Chromagram, CpalChordSource};
+use cpal::traits::HostTrait;
+
+#[test]
+fn test_cpal_source_builds_on_default_device() {
+    let Some(device) = cpal::default_host().default_input_device() else {
+        eprintln!("no input device, skipping");
+        return;
+    };
+
+    match CpalChordSource::build(&device, Chromagram::builder(), ChordDetector::builder()) {
+        Ok(source) => {
+            assert!(source.sample_rate() > 0);
+            let _ = source.try_recv();
+        }
+        // Present but busy or unusable devices are an environment issue
+        Err(e) => eprintln!("input device unavailable, skipping: {e}"),
+    }
+}
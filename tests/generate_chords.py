@@ -29,6 +29,8 @@ CHORD_TEMPLATES = {
     "aug":  [0, 4, 8],
     "sus2": [0, 2, 7],
     "sus4": [0, 5, 7],
+    "m7b5": [0, 3, 6, 10],
+    "dim7": [0, 3, 6, 9],
 }
 
 os.makedirs(OUT, exist_ok=True)
@@ -0,0 +1,82 @@
+use chord_detector::{spell, KeyEstimator, KeyMode, NoteName};
+
+/// Major-scale chroma on `tonic` with the tonic triad emphasized.
+fn major_scale(tonic: usize) -> [f32; 12] {
+    let mut chroma = [0.0; 12];
+    for (step, weight) in [(0, 2.0), (2, 1.0), (4, 1.5), (5, 1.0), (7, 1.5), (9, 1.0), (11, 1.0)] {
+        chroma[(tonic + step) % 12] = weight;
+    }
+    chroma
+}
+
+fn signature(tonic: usize) -> (i8, KeyMode) {
+    let mut estimator = KeyEstimator::new();
+    for _ in 0..4 {
+        estimator.add_frame(&major_scale(tonic));
+    }
+    estimator.key_signature()
+}
+
+#[test]
+fn test_key_signature_major_keys() {
+    assert_eq!(signature(0), (0, KeyMode::Major)); // C
+    assert_eq!(signature(7), (1, KeyMode::Major)); // G
+    assert_eq!(signature(5), (-1, KeyMode::Major)); // F
+    assert_eq!(signature(2), (2, KeyMode::Major)); // D
+    assert_eq!(signature(10), (-2, KeyMode::Major)); // Bb
+}
+
+#[test]
+fn test_key_signature_minor_and_empty() {
+    // A natural minor with the A minor triad emphasized
+    let mut chroma = [0.0; 12];
+    for (pc, weight) in [(9, 2.0), (11, 1.0), (0, 1.5), (2, 1.0), (4, 1.5), (5, 1.0), (7, 1.0)] {
+        chroma[pc] = weight;
+    }
+    let mut estimator = KeyEstimator::new();
+    estimator.add_frame(&chroma);
+    assert_eq!(estimator.key_signature(), (0, KeyMode::Minor));
+
+    estimator.reset();
+    assert_eq!(estimator.estimate(), None);
+    assert_eq!(estimator.key_signature(), (0, KeyMode::Major));
+}
+
+#[test]
+fn test_estimate_key_and_finalize_c_major() {
+    // C major scale, tonic triad emphasized
+    let scale = major_scale(0);
+    let mut estimator = KeyEstimator::new();
+    assert_eq!(estimator.estimate_key(&scale), Some((NoteName::C, KeyMode::Major)));
+    assert_eq!(estimator.estimate_key(&[0.0; 12]), None);
+    assert_eq!(estimator.estimate(), None);
+
+    for _ in 0..8 {
+        estimator.add_frame(&scale);
+    }
+    assert_eq!(estimator.finalize(), Some((NoteName::C, KeyMode::Major)));
+    assert_eq!(estimator.finalize(), None);
+}
+
+#[test]
+fn test_spell_follows_key_signature() {
+    use KeyMode::{Major, Minor};
+    use NoteName::*;
+
+    // The same pitch classes in a flat key and a sharp key
+    assert_eq!(spell(As, F, Major), "Bb");
+    assert_eq!(spell(As, B, Major), "A#");
+    assert_eq!(spell(Fs, G, Major), "F#");
+    assert_eq!(spell(Fs, Cs, Major), "Gb");
+    assert_eq!(spell(As, D, Minor), "Bb");
+
+    // Naturals, chromatic notes and letters that need E# or Cb
+    assert_eq!(spell(C, F, Major), "C");
+    assert_eq!(spell(Ds, F, Major), "Eb");
+    assert_eq!(spell(Ds, G, Major), "D#");
+    assert_eq!(spell(F, Fs, Major), "E#");
+    assert_eq!(spell(B, Gs, Minor), "B");
+
+    assert_eq!(spell(Unknown, F, Major), "?");
+    assert_eq!(spell(As, Unknown, Major), "A#");
+}
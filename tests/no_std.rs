@@ -0,0 +1,18 @@
+//! Build check for the `no_std` + `alloc` configuration.
+
+use std::path::Path;
+use std::process::Command;
+
+/// Runs a nested `cargo check`, so it is opt-in: `cargo test -- --ignored`.
+#[test]
+#[ignore]
+fn test_builds_without_std() {
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let status = Command::new(env!("CARGO"))
+        .args(["check", "--lib", "--no-default-features", "--features", "alloc"])
+        .current_dir(manifest_dir)
+        .env("CARGO_TARGET_DIR", manifest_dir.join("target/no-std-check"))
+        .status()
+        .expect("failed to run cargo");
+    assert!(status.success());
+}
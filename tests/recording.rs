@@ -0,0 +1,36 @@
+#![cfg(feature = "serde")]
+
+use chord_detector::{ChordDetector, ChordKind, ChromaRecording, NoteName};
+
+fn chroma_of(notes: &[usize]) -> [f32; 12] {
+    let mut chroma = [0.0; 12];
+    for &n in notes {
+        chroma[n] = 1.0;
+    }
+    chroma
+}
+
+#[test]
+fn recording_round_trips_through_json_and_replays() {
+    let mut recording = ChromaRecording::new();
+    recording.push(0.0, chroma_of(&[0, 4, 7]));
+    recording.push(0.5, chroma_of(&[9, 0, 4]));
+    recording.push(1.0, chroma_of(&[7, 11, 2]));
+
+    let json = recording.to_json().unwrap();
+    let restored = ChromaRecording::from_json(&json).unwrap();
+    assert_eq!(restored, recording);
+
+    let mut detector = ChordDetector::new();
+    let first = restored.replay(&mut detector);
+    let second = restored.replay(&mut detector);
+    assert_eq!(first, second);
+
+    assert_eq!(first.len(), 3);
+    assert_eq!(first[0].chord.root, NoteName::C);
+    assert_eq!(first[0].chord.quality, ChordKind::Major);
+    assert_eq!(first[1].chord.root, NoteName::A);
+    assert_eq!(first[1].chord.quality, ChordKind::Minor);
+    assert_eq!((first[1].start, first[1].end), (0.5, 1.0));
+    assert_eq!((first[2].start, first[2].end), (1.0, 1.5));
+}
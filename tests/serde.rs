@@ -0,0 +1,41 @@
+#![cfg(feature = "serde")]
+
+use chord_detector::{
+    Chord, ChordDetector, ChordKind, ChordTemplate, DetectorConfig, NoteName, TemplateModel,
+};
+
+#[test]
+fn chord_round_trips_with_readable_names() {
+    let chord = Chord {
+        root: NoteName::Cs,
+        quality: ChordKind::DominantSeventh,
+        confidence: 0.25,
+        bass: Some(NoteName::F),
+    };
+    let json = serde_json::to_string(&chord).unwrap();
+    assert_eq!(
+        json,
+        r#"{"root":"c_sharp","quality":"dominant_seventh","confidence":0.25,"bass":"f"}"#
+    );
+    assert_eq!(serde_json::from_str::<Chord>(&json).unwrap(), chord);
+
+    assert_eq!(serde_json::to_string(&ChordKind::Major).unwrap(), r#""major""#);
+    assert_eq!(serde_json::to_string(&ChordKind::Custom(2)).unwrap(), r#"{"custom":2}"#);
+    assert_eq!(serde_json::to_string(&NoteName::Unknown).unwrap(), r#""unknown""#);
+    assert_eq!(serde_json::from_str::<NoteName>(r#""a_sharp""#).unwrap(), NoteName::As);
+}
+
+#[test]
+fn detector_config_round_trips() {
+    let detector = ChordDetector::builder()
+        .bleed(0.2)
+        .templates(vec![ChordTemplate::new("add9", &[0, 4, 7, 2], 1.0)])
+        .template_model(TemplateModel::Harmonic)
+        .build();
+    let config = detector.config();
+    let json = serde_json::to_string(&config).unwrap();
+    assert!(json.contains(r#""template_model":"harmonic""#));
+    let restored: DetectorConfig = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored, config);
+    assert_eq!(ChordDetector::from_config(restored).config(), config);
+}
@@ -0,0 +1,45 @@
+//! Tests for the sample-stream chord iterator.
+#![cfg(feature = "chromagram")]
+
+use chord_detector::{ChordDetector, ChordKind, ChordStream, Chromagram, NoteName};
+use std::f32::consts::PI;
+
+const SAMPLE_RATE: usize = 44_100;
+
+#[test]
+fn test_chord_stream_yields_c_major() {
+    let freqs = [261.63, 329.63, 392.0];
+    let samples = (0..2 * SAMPLE_RATE).map(move |i| {
+        let t = i as f32 / SAMPLE_RATE as f32;
+        freqs.iter().map(|f| (2.0 * PI * f * t).sin()).sum::<f32>() / 3.0
+    });
+
+    let chromagram = Chromagram::builder().build().unwrap();
+    let stream = ChordStream::from_samples(samples, chromagram, ChordDetector::new());
+    let chords: Vec<_> = stream.map(Result::unwrap).collect();
+
+    // One chord per emitted chromagram, two seconds at the default hop
+    assert_eq!(chords.len(), 2 * SAMPLE_RATE / 1024 / 4);
+    let last = chords.last().unwrap();
+    assert_eq!((last.root, last.quality), (NoteName::C, ChordKind::Major));
+}
+
+#[test]
+fn test_onsets_only_yields_one_chord_per_onset() {
+    let freqs = [261.63, 329.63, 392.0];
+    let samples = (0..4 * SAMPLE_RATE).map(move |i| {
+        if i < SAMPLE_RATE {
+            return 0.0;
+        }
+        let t = i as f32 / SAMPLE_RATE as f32;
+        freqs.iter().map(|f| (2.0 * PI * f * t).sin()).sum::<f32>() / 3.0
+    });
+
+    let chromagram = Chromagram::builder().build().unwrap();
+    let stream = ChordStream::from_samples(samples, chromagram, ChordDetector::new())
+        .onsets_only(true);
+    let chords: Vec<_> = stream.map(Result::unwrap).collect();
+
+    assert_eq!(chords.len(), 1);
+    assert_eq!((chords[0].root, chords[0].quality), (NoteName::C, ChordKind::Major));
+}
@@ -0,0 +1,54 @@
+//! Tests for grading detected chord timelines against a reference chart.
+
+use chord_detector::{
+    compare_timeline, compare_timeline_with_tolerance, Chord, ChordKind, NoteName, TimedChord,
+};
+
+fn timed(root: NoteName, quality: ChordKind, start: f64, end: f64) -> TimedChord {
+    TimedChord {
+        chord: Chord {
+            root,
+            quality,
+            confidence: 0.0,
+            bass: None,
+        },
+        start,
+        end,
+    }
+}
+
+#[test]
+fn test_compare_timeline_partial_overlap() {
+    let reference = [
+        timed(NoteName::C, ChordKind::Major, 0.0, 2.0),
+        timed(NoteName::A, ChordKind::Minor, 2.0, 4.0),
+    ];
+    let detected = [
+        timed(NoteName::C, ChordKind::Major, 0.0, 2.5),
+        timed(NoteName::A, ChordKind::Minor, 2.5, 3.0),
+        timed(NoteName::F, ChordKind::Major, 3.0, 4.0),
+    ];
+
+    let score = compare_timeline(&detected, &reference);
+    assert_eq!(score.per_chord, vec![1.0, 0.25]);
+    assert!((score.overall - 2.5 / 4.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_compare_timeline_tolerance() {
+    let reference = [
+        timed(NoteName::C, ChordKind::Major, 0.0, 2.0),
+        timed(NoteName::G, ChordKind::Major, 2.0, 4.0),
+    ];
+    // Detection lags the change by 0.1 s
+    let detected = [
+        timed(NoteName::C, ChordKind::Major, 0.0, 2.1),
+        timed(NoteName::G, ChordKind::Major, 2.1, 4.0),
+    ];
+
+    let strict = compare_timeline(&detected, &reference);
+    assert!(strict.overall < 1.0);
+
+    let lenient = compare_timeline_with_tolerance(&detected, &reference, 0.1);
+    assert!((lenient.overall - 1.0).abs() < 1e-9);
+}
@@ -0,0 +1,58 @@
+//! Tests for the chord commit/hold tracker.
+
+use chord_detector::{ChordDetector, ChordError, ChordKind, ChordTracker, NoteName};
+
+/// Build a chromagram with unit energy on each of `notes` (semitones from C).
+fn chroma_of(notes: &[usize]) -> [f32; 12] {
+    let mut chroma = [0.0; 12];
+    for &n in notes {
+        chroma[n % 12] = 1.0;
+    }
+    chroma
+}
+
+#[test]
+fn test_noisy_transition_commits_once() {
+    let c_major = chroma_of(&[0, 4, 7]);
+    let a_minor = chroma_of(&[9, 0, 4]);
+    // C settles, then the change to Am flickers back before it sticks
+    let frames = [
+        c_major, c_major, c_major, c_major, a_minor, c_major, a_minor, a_minor, c_major, a_minor,
+        a_minor, a_minor, a_minor, a_minor,
+    ];
+
+    let mut tracker = ChordTracker::builder().commit_frames(3).release_frames(3).build();
+    let mut commits = Vec::new();
+    for (i, frame) in frames.iter().enumerate() {
+        if let Some(chord) = tracker.update(frame).unwrap() {
+            commits.push((i, chord.root, chord.quality));
+        }
+    }
+    assert_eq!(
+        commits,
+        [(2, NoteName::C, ChordKind::Major), (11, NoteName::A, ChordKind::Minor)]
+    );
+    let held = tracker.committed().unwrap();
+    assert_eq!((held.root, held.quality), (NoteName::A, ChordKind::Minor));
+
+    // Frame by frame the detector itself flips at every flicker
+    let mut detector = ChordDetector::new();
+    let roots: Vec<_> = frames.iter().map(|f| detector.detect_chord(f).unwrap().root).collect();
+    let changes = roots.windows(2).filter(|w| w[0] != w[1]).count();
+    assert_eq!(changes, 5);
+
+    tracker.reset();
+    assert!(tracker.committed().is_none());
+}
+
+#[test]
+fn test_tracker_rejects_zero_thresholds() {
+    assert!(matches!(
+        ChordTracker::builder().commit_frames(0).try_build(),
+        Err(ChordError::InvalidArgument { arg: "commit_frames", .. })
+    ));
+    assert!(matches!(
+        ChordTracker::builder().release_frames(0).try_build(),
+        Err(ChordError::InvalidArgument { arg: "release_frames", .. })
+    ));
+}
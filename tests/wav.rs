@@ -0,0 +1,67 @@
+//! Tests for WAV file analysis.
+#![cfg(feature = "hound")]
+
+use chord_detector::{analyze_wav, ChordDetector, ChordKind, Chromagram, NoteName};
+use hound::{SampleFormat, WavSpec, WavWriter};
+use std::f32::consts::PI;
+use std::path::PathBuf;
+
+const SAMPLE_RATE: u32 = 48_000;
+
+fn c_major(i: u32) -> f32 {
+    let t = i as f32 / SAMPLE_RATE as f32;
+    [261.63f32, 329.63, 392.0]
+        .iter()
+        .map(|f| (2.0 * PI * f * t).sin())
+        .sum::<f32>()
+        / 3.0
+}
+
+/// Write two seconds of a C major triad, duplicated over `channels`
+fn write_c_major(name: &str, channels: u16, bits: u16, format: SampleFormat) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("chord_detector_{name}.wav"));
+    let spec = WavSpec {
+        channels,
+        sample_rate: SAMPLE_RATE,
+        bits_per_sample: bits,
+        sample_format: format,
+    };
+    let mut writer = WavWriter::create(&path, spec).unwrap();
+    let peak = ((1i64 << (bits - 1)) - 1) as f32;
+    for i in 0..2 * SAMPLE_RATE {
+        let s = c_major(i);
+        for _ in 0..channels {
+            match format {
+                SampleFormat::Float => writer.write_sample(s).unwrap(),
+                SampleFormat::Int => writer.write_sample((s * peak) as i32).unwrap(),
+            }
+        }
+    }
+    writer.finalize().unwrap();
+    path
+}
+
+fn assert_c_major(path: PathBuf) {
+    let chords = analyze_wav(&path, Chromagram::builder(), ChordDetector::builder()).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    // The header rate was used: 2 s at 48 kHz with the default hop
+    assert_eq!(chords.len(), 2 * SAMPLE_RATE as usize / 1024 / 4);
+    let last = chords.last().unwrap();
+    assert_eq!((last.root, last.quality), (NoteName::C, ChordKind::Major));
+}
+
+#[test]
+fn test_analyze_wav_stereo_16_bit() {
+    assert_c_major(write_c_major("stereo_i16", 2, 16, SampleFormat::Int));
+}
+
+#[test]
+fn test_analyze_wav_mono_24_bit() {
+    assert_c_major(write_c_major("mono_i24", 1, 24, SampleFormat::Int));
+}
+
+#[test]
+fn test_analyze_wav_stereo_float() {
+    assert_c_major(write_c_major("stereo_f32", 2, 32, SampleFormat::Float));
+}